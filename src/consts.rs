@@ -1,2 +1,6 @@
 pub const BELT_THROUGHPUT: f64 = 45.0;
-pub const LIQUID_EQ_STACK_SIZE: f64 = 625.0;
\ No newline at end of file
+pub const LANE_THROUGHPUT: f64 = BELT_THROUGHPUT / 2.0;
+pub const LIQUID_EQ_STACK_SIZE: f64 = 625.0;
+pub const PIPE_THROUGHPUT: f64 = 1200.0;
+pub const CARGO_WAGON_STACKS: f64 = 40.0;
+pub const FLUID_WAGON_CAPACITY: f64 = 25000.0;
@@ -1,9 +1,11 @@
 pub mod graph;
+pub mod mermaid;
+pub mod svg;
 pub mod text;
 
 use failure::{format_err, Error};
 
-use crate::solution::Solution;
+use crate::solution::{Solution, Throughput};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -14,7 +16,20 @@ pub trait Formatter {
 pub fn formatter_by_name(name: &str) -> Result<Box<dyn Formatter>> {
     match name {
         "graph" => Ok(Box::new(graph::GraphFormatter::new())),
+        "mermaid" => Ok(Box::new(mermaid::MermaidFormatter::new())),
+        "svg" => Ok(Box::new(svg::SvgFormatter::new())),
         "text" => Ok(Box::new(text::TextFormatter::new())),
         name => Err(format_err!("unknown formatter: {}", name)),
     }
 }
+
+/// Warns about shortfalls on stderr for formatters drawing diagrams on stdout.
+fn warn_shortfalls(shortfalls: &[Throughput]) {
+    for s in shortfalls {
+        eprintln!(
+            "WARNING: shortfall of {}: {:.2}/s above the source limit.",
+            s.name(),
+            s.throughput()
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::consts::BELT_THROUGHPUT;
 use crate::solution::*;
 use crate::util::F64Extra;
 
-use super::{Formatter, Result};
+use super::{warn_shortfalls, Formatter, Result};
 
 pub struct GraphFormatter {
     counter: Counter,
@@ -15,6 +15,7 @@ impl Formatter for GraphFormatter {
         if !solution.missings.is_empty() {
             return Err(format_err!("missing sources: {:?}", solution.missings));
         }
+        warn_shortfalls(&solution.shortfalls);
 
         println!("digraph solutinon {{");
 
@@ -0,0 +1,179 @@
+use failure::format_err;
+
+use crate::solution::*;
+use crate::util::F64Extra;
+
+use super::{warn_shortfalls, Formatter, Result};
+
+pub struct MermaidFormatter {
+    counter: Counter,
+}
+
+impl Formatter for MermaidFormatter {
+    fn format(&mut self, solution: &Solution) -> Result<()> {
+        if !solution.missings.is_empty() {
+            return Err(format_err!("missing sources: {:?}", solution.missings));
+        }
+        warn_shortfalls(&solution.shortfalls);
+
+        println!("flowchart RL");
+
+        self.format_sources(&solution.sources);
+
+        for p in &solution.trees {
+            println!();
+            self.format_proc_tree(p);
+        }
+
+        Ok(())
+    }
+}
+
+impl MermaidFormatter {
+    pub fn new() -> MermaidFormatter {
+        MermaidFormatter {
+            counter: Counter::new(),
+        }
+    }
+
+    fn format_proc_tree(&mut self, proc_tree: &ProcessingTree) {
+        let process = &proc_tree.process;
+        let id = root_proc_id(process.throughput.name());
+
+        println!("    %% processing tree {}", process.throughput.name());
+        self.format_proc_node(&id, process);
+
+        for s in &process.sources {
+            self.format_source(s, &id);
+        }
+    }
+
+    fn format_proc(&mut self, process: &Process) -> String {
+        let id = nested_proc_id(process.throughput.name(), self.counter.next_mid());
+
+        self.format_proc_node(&id, process);
+
+        for s in &process.sources {
+            self.format_source(s, &id);
+        }
+
+        id
+    }
+
+    fn format_proc_node(&mut self, id: &str, process: &Process) {
+        println!(
+            "    {id}[\"{n}<br/>{p} x {c}\"]",
+            id = id,
+            n = label_escape(process.throughput.name()),
+            p = label_escape(process.processer.name()),
+            c = process.processer_num,
+        );
+    }
+
+    fn format_source(&mut self, source: &Source, to_id: &str) {
+        match source {
+            Source::Process(process) => {
+                let ch_id = self.format_proc(process);
+                self.format_edge(&ch_id, to_id, &process.throughput);
+            }
+            Source::Merged(th) => {
+                let from_id = root_proc_id(th.name());
+                self.format_edge(&from_id, to_id, th);
+            }
+            Source::Source(th) => {
+                let from_id = format!("source_{}", node_id(th.name()));
+                self.format_edge(&from_id, to_id, th);
+            }
+        }
+    }
+
+    fn format_edge(&mut self, from_id: &str, to_id: &str, th: &Throughput) {
+        println!(
+            "    {f} -->|\"{l}\"| {t}",
+            f = from_id,
+            t = to_id,
+            l = edge_label(th),
+        );
+    }
+
+    fn format_sources(&mut self, sources: &[Throughput]) {
+        println!("    %% source nodes");
+
+        for s in sources {
+            println!(
+                "    source_{id}((\"{n}\"))",
+                id = node_id(s.name()),
+                n = label_escape(s.name()),
+            );
+        }
+    }
+}
+
+fn node_id(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn root_proc_id(name: &str) -> String {
+    format!("proc_{}", node_id(name))
+}
+
+/// The counter goes first, so an item name ending in a digit can't make the id of
+/// another process.
+fn nested_proc_id(name: &str, n: usize) -> String {
+    format!("p{}_{}", n, node_id(name))
+}
+
+fn label_escape(label: &str) -> String {
+    label.replace("\"", "#quot;")
+}
+
+fn edge_label(th: &Throughput) -> String {
+    match th {
+        Throughput::Item(_, t) => format!("{:.2}/s", t.ceil_at(-2)),
+        Throughput::Liquid(_, t) => format!("{:.1} unit/s", t.ceil_at(-1)),
+        Throughput::Power(_, t) => format!("{:.1} MW", t.ceil_at(-1)),
+    }
+}
+
+struct Counter {
+    mid_count: usize,
+}
+
+impl Counter {
+    fn new() -> Counter {
+        Counter { mid_count: 0 }
+    }
+
+    fn next_mid(&mut self) -> usize {
+        let v = self.mid_count;
+        self.mid_count += 1;
+        v
+    }
+}
+
+#[test]
+fn node_id_is_safe() {
+    let params = [
+        ("iron-plate", "iron_plate"),
+        ("speed-module-2", "speed_module_2"),
+        ("a b.c\"d", "a_b_c_d"),
+    ];
+
+    for p in &params {
+        assert_eq!(node_id(p.0), p.1);
+    }
+}
+
+#[test]
+fn nested_proc_id_is_unique() {
+    assert_ne!(
+        nested_proc_id("speed-module", 2),
+        root_proc_id("speed-module-2")
+    );
+    assert_ne!(
+        nested_proc_id("speed-module-2", 1),
+        nested_proc_id("speed-module", 21)
+    );
+}
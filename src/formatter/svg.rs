@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use failure::format_err;
+
+use crate::consts::{BELT_THROUGHPUT, PIPE_THROUGHPUT};
+use crate::solution::*;
+use crate::util::F64Extra;
+
+use super::{warn_shortfalls, Formatter, Result};
+
+const MARGIN: f64 = 20.0;
+const COLUMN_WIDTH: f64 = 260.0;
+const NODE_WIDTH: f64 = 16.0;
+const NODE_GAP: f64 = 40.0;
+const MIN_NODE_HEIGHT: f64 = 8.0;
+const MIN_EDGE_WIDTH: f64 = 1.0;
+const MAX_EDGE_WIDTH: f64 = 60.0;
+
+pub struct SvgFormatter();
+
+impl Formatter for SvgFormatter {
+    fn format(&mut self, solution: &Solution) -> Result<()> {
+        print!("{}", self.svg(solution)?);
+
+        Ok(())
+    }
+}
+
+impl SvgFormatter {
+    pub fn new() -> SvgFormatter {
+        SvgFormatter()
+    }
+
+    fn svg(&self, solution: &Solution) -> Result<String> {
+        if !solution.missings.is_empty() {
+            return Err(format_err!("missing sources: {:?}", solution.missings));
+        }
+        warn_shortfalls(&solution.shortfalls);
+
+        let graph = &solution.graph;
+        let mut layout = Layout::new();
+
+        for i in &graph.items {
+            let idx = layout.node(&item_key(i.throughput.name()));
+            let node = &mut layout.nodes[idx];
+            node.label = i.throughput.name().to_string();
+            node.detail = throughput_label(&i.throughput);
+        }
+
+        for (idx, r) in graph.recipes.iter().enumerate() {
+            let idx = layout.node(&recipe_key(idx));
+            let node = &mut layout.nodes[idx];
+            node.label = r.recipe.clone();
+            node.detail = format!("{} x {}", r.processer.name(), r.processer_num);
+        }
+
+        for f in graph.flows() {
+            let from = layout.node(&node_key(&f.from));
+            let to = layout.node(&node_key(&f.to));
+            layout.edge(from, to, &f.throughput);
+        }
+
+        layout.arrange();
+
+        let mut out = String::new();
+        layout.render(&mut out)?;
+        Ok(out)
+    }
+}
+
+fn node_key(node: &NodeRef) -> String {
+    match node {
+        NodeRef::Item(n) => item_key(n),
+        NodeRef::Recipe(idx) => recipe_key(*idx),
+    }
+}
+
+fn item_key(name: &str) -> String {
+    format!("item_{}", name)
+}
+
+fn recipe_key(idx: usize) -> String {
+    format!("recipe_{}", idx)
+}
+
+fn throughput_label(th: &Throughput) -> String {
+    match th {
+        Throughput::Item(_, t) => format!("{:.2} item/s", t.ceil_at(-2)),
+        Throughput::Liquid(_, t) => format!("{:.1} unit/s", t.ceil_at(-1)),
+        Throughput::Power(_, t) => format!("{:.1} MW", t.ceil_at(-1)),
+    }
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    label: String,
+    detail: String,
+    column: usize,
+    inflow: f64,
+    outflow: f64,
+    x: f64,
+    y: f64,
+    in_cursor: f64,
+    out_cursor: f64,
+}
+
+impl Node {
+    fn height(&self) -> f64 {
+        self.inflow.max(self.outflow).max(MIN_NODE_HEIGHT)
+    }
+}
+
+#[derive(Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    belts: f64,
+    width: f64,
+    label: String,
+    liquid: bool,
+}
+
+#[derive(Debug)]
+struct Layout {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    index: HashMap<String, usize>,
+    width: f64,
+    height: f64,
+}
+
+impl Layout {
+    fn new() -> Layout {
+        Layout {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            index: HashMap::new(),
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    fn node(&mut self, key: &str) -> usize {
+        if let Some(&idx) = self.index.get(key) {
+            return idx;
+        }
+
+        self.nodes.push(Node::default());
+        let idx = self.nodes.len() - 1;
+        self.index.insert(key.to_string(), idx);
+        idx
+    }
+
+    fn edge(&mut self, from: usize, to: usize, th: &Throughput) {
+        let (belts, liquid) = match th {
+            Throughput::Item(_, t) => (t / BELT_THROUGHPUT, false),
+            Throughput::Liquid(_, t) => (t / PIPE_THROUGHPUT, true),
+            // Power goes over wires, drawn with the narrowest width.
+            Throughput::Power(_, _) => (0.0, false),
+        };
+        let label = format!("{}: {}", th.name(), throughput_label(th));
+
+        self.edges.push(Edge {
+            from,
+            to,
+            belts,
+            width: 0.0,
+            label,
+            liquid,
+        });
+    }
+
+    fn arrange(&mut self) {
+        // Edge widths are proportional to throughput, scaled so the widest edge fits.
+        let max_belts = self.edges.iter().map(|e| e.belts).fold(0.0, f64::max);
+        let scale = if max_belts > 0.0 {
+            MAX_EDGE_WIDTH / max_belts
+        } else {
+            0.0
+        };
+        for e in &mut self.edges {
+            e.width = (e.belts * scale).max(MIN_EDGE_WIDTH);
+            self.nodes[e.from].outflow += e.width;
+            self.nodes[e.to].inflow += e.width;
+        }
+
+        // Longest path from sources; edges always point towards the targets.
+        for _ in 0..self.nodes.len() {
+            let mut changed = false;
+            for e in &self.edges {
+                let c = self.nodes[e.from].column + 1;
+                if self.nodes[e.to].column < c {
+                    self.nodes[e.to].column = c;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let max_column = self.nodes.iter().map(|n| n.column).max().unwrap_or(0);
+        for (i, n) in self.nodes.iter_mut().enumerate() {
+            if !self.edges.iter().any(|e| e.from == i) {
+                n.column = max_column;
+            }
+        }
+
+        let mut column_heights = vec![MARGIN; max_column + 1];
+        for n in &mut self.nodes {
+            n.x = MARGIN + (n.column as f64) * COLUMN_WIDTH;
+            n.y = column_heights[n.column];
+            column_heights[n.column] += n.height() + NODE_GAP;
+        }
+
+        self.width = 2.0 * MARGIN + (max_column as f64) * COLUMN_WIDTH + COLUMN_WIDTH;
+        self.height = column_heights.iter().cloned().fold(0.0, f64::max) + MARGIN;
+    }
+
+    fn render(&mut self, out: &mut String) -> std::fmt::Result {
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="sans-serif" font-size="12">"#,
+            w = self.width,
+            h = self.height,
+        )?;
+
+        for i in 0..self.edges.len() {
+            self.render_edge(i, out)?;
+        }
+
+        for n in &self.nodes {
+            writeln!(
+                out,
+                r##"  <rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="#4a6fa5"/>"##,
+                x = n.x,
+                y = n.y,
+                w = NODE_WIDTH,
+                h = n.height(),
+            )?;
+            writeln!(
+                out,
+                r#"  <text x="{x:.1}" y="{y:.1}">{l}</text>"#,
+                x = n.x + NODE_WIDTH + 4.0,
+                y = n.y + 12.0,
+                l = xml_escape(&n.label),
+            )?;
+            if !n.detail.is_empty() {
+                writeln!(
+                    out,
+                    r##"  <text x="{x:.1}" y="{y:.1}" fill="#666">{d}</text>"##,
+                    x = n.x + NODE_WIDTH + 4.0,
+                    y = n.y + 26.0,
+                    d = xml_escape(&n.detail),
+                )?;
+            }
+        }
+
+        writeln!(out, "</svg>")
+    }
+
+    fn render_edge(&mut self, i: usize, out: &mut String) -> std::fmt::Result {
+        let (from, to, width) = {
+            let e = &self.edges[i];
+            (e.from, e.to, e.width)
+        };
+
+        let x1 = self.nodes[from].x + NODE_WIDTH;
+        let y1 = self.nodes[from].y + self.nodes[from].out_cursor + width / 2.0;
+        self.nodes[from].out_cursor += width;
+
+        let x2 = self.nodes[to].x;
+        let y2 = self.nodes[to].y + self.nodes[to].in_cursor + width / 2.0;
+        self.nodes[to].in_cursor += width;
+
+        let mx = (x1 + x2) / 2.0;
+        let e = &self.edges[i];
+        let color = if e.liquid { "#3fa7d6" } else { "#e0a458" };
+
+        writeln!(
+            out,
+            r#"  <path d="M{x1:.1},{y1:.1} C{mx:.1},{y1:.1} {mx:.1},{y2:.1} {x2:.1},{y2:.1}" fill="none" stroke="{c}" stroke-opacity="0.5" stroke-width="{w:.1}"><title>{t}</title></path>"#,
+            x1 = x1,
+            y1 = y1,
+            x2 = x2,
+            y2 = y2,
+            mx = mx,
+            c = color,
+            w = width,
+            t = xml_escape(&e.label),
+        )
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
+
+#[test]
+fn svg_has_nodes_and_edges() {
+    use crate::processer::ProcSet;
+    use crate::recipe::load_recipes;
+    use crate::solver::Solver;
+    use crate::target::TargetSettings;
+
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("iron-gear-wheel".to_string(), 1.0);
+    target_settings.add_source("iron-plate".to_string());
+    let solver = Solver::new(
+        load_recipes("./data/recipes").unwrap(),
+        &target_settings,
+        ProcSet::open_set().unwrap(),
+    );
+
+    let svg = SvgFormatter::new().svg(&solver.solve().unwrap()).unwrap();
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.ends_with("</svg>\n"));
+    // two items and the recipe between them
+    assert_eq!(svg.matches("<rect ").count(), 3);
+    assert!(svg.contains(">iron-gear-wheel</text>"));
+    assert!(svg.contains(">iron-plate</text>"));
+    assert_eq!(svg.matches("<path ").count(), 2);
+    assert!(svg.contains("<title>iron-plate: "));
+}
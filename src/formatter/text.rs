@@ -1,4 +1,5 @@
 use crate::consts::BELT_THROUGHPUT;
+use crate::oil::OilBalance;
 use crate::solution::*;
 use crate::util::F64Extra;
 
@@ -12,11 +13,38 @@ impl Formatter for TextFormatter {
             println!();
             self.format_proc_tree(p);
         }
+        println!();
+        self.format_summary(&solution.summary());
+
+        if !solution.auto_merged.is_empty() {
+            println!();
+            self.format_auto_merged(&solution.auto_merged);
+        }
+
+        if let Some(ref oil) = solution.oil {
+            println!();
+            self.format_oil(oil);
+        }
+
+        if let Some(ref fuel) = solution.fuel {
+            println!();
+            println!("Fuel for burner processers:");
+            self.indent(1);
+            self.format_throughput(fuel);
+            println!();
+        }
+
+        if let Some(ref fit) = solution.fit {
+            println!();
+            self.format_fit(fit);
+        }
+
         println!();
         self.format_sources(&solution.sources);
 
         println!();
         self.format_missings(&solution.missings);
+        self.format_shortfalls(&solution.shortfalls);
 
         Ok(())
     }
@@ -68,6 +96,71 @@ impl TextFormatter {
         }
     }
 
+    fn format_summary(&mut self, summary: &[ItemSummary]) {
+        println!("Summary:");
+
+        for s in summary {
+            print!("    ");
+            self.format_throughput(&s.throughput);
+            println!(
+                ": {} with {} {} units, {:.2} craft/s",
+                s.recipe,
+                s.processer.name(),
+                s.processer_num,
+                s.craft_per_sec.ceil_at(-2),
+            );
+        }
+    }
+
+    fn format_auto_merged(&mut self, auto_merged: &[AutoMerged]) {
+        println!("Auto merged:");
+
+        for m in auto_merged {
+            println!("    {}: {}", m.name, m.reason);
+        }
+    }
+
+    fn format_oil(&mut self, oil: &OilBalance) {
+        println!("Oil balance:");
+
+        for p in oil.processes() {
+            println!(
+                "    {}: {} {} units, {:.2} craft/s",
+                p.recipe,
+                p.processer.name(),
+                p.processer_num,
+                p.craft_per_sec.ceil_at(-2),
+            );
+        }
+
+        for s in &oil.surplus {
+            print!("    surplus of ");
+            self.format_throughput(s);
+            println!();
+        }
+    }
+
+    fn format_fit(&mut self, fit: &Fit) {
+        println!("Fitted targets: x{:.3}, bound by {}", fit.mult, fit.binding);
+
+        for u in &fit.supplies {
+            print!("    supply of ");
+            self.format_throughput(&Throughput::new(u.name.clone(), u.used));
+            println!(" of {:.2} ({:.0}%)", u.supplied, 100.0 * u.load());
+        }
+
+        for p in &fit.pins {
+            println!(
+                "    pinned {}: {} {:.2} of {} units ({:.0}%)",
+                p.name,
+                p.processer,
+                p.machines,
+                p.count,
+                100.0 * p.load()
+            );
+        }
+    }
+
     fn format_sources(&mut self, sources: &[Throughput]) {
         println!("Source throughputs:");
 
@@ -87,6 +180,7 @@ impl TextFormatter {
                 (t / BELT_THROUGHPUT).ceil_at(-1)
             ),
             Throughput::Liquid(n, t) => print!("{}: {:.2} unit/s", n, t.ceil_at(-2)),
+            Throughput::Power(n, t) => print!("{}: {:.2} MW", n, t.ceil_at(-2)),
         }
     }
 
@@ -109,6 +203,14 @@ impl TextFormatter {
         }
     }
 
+    fn format_shortfalls(&mut self, shortfalls: &[Throughput]) {
+        for s in shortfalls {
+            print!("WARNING: shortfall of ");
+            self.format_throughput(s);
+            println!(" above the source limit.");
+        }
+    }
+
     fn indent(&mut self, i: usize) {
         for _ in 0..i {
             print!("    ");
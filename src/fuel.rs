@@ -0,0 +1,24 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::BufReader;
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+/// Fuel values of items in MJ.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FuelDict(HashMap<String, f64>);
+
+impl FuelDict {
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0.get(name).cloned()
+    }
+}
+
+pub fn load_fuel_dict(file_path: &str) -> Result<FuelDict, Error> {
+    let file = fs::File::open(file_path)?;
+    let reader = BufReader::new(file);
+
+    let dict: FuelDict = serde_yaml::from_reader(reader)?;
+    Ok(dict)
+}
@@ -3,14 +3,20 @@ use failure::{format_err, Error};
 
 mod consts;
 mod formatter;
+mod fuel;
 mod near_name;
+mod oil;
 mod processer;
 mod recipe;
+mod research;
 mod solution;
 mod solver;
+mod source_set;
 mod stack;
 mod sub;
 mod target;
+mod technology;
+mod transport;
 mod util;
 
 use sub::{sub_commands, SubCmd};
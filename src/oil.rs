@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use failure::{format_err, Error};
+
+use crate::processer::{ProcSet, Processer, ProcesserChoice};
+use crate::recipe::{Recipe, RecipeSet};
+use crate::solution::Throughput;
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub const HEAVY_OIL: &str = "heavy-oil";
+pub const LIGHT_OIL: &str = "light-oil";
+pub const PETROLEUM_GAS: &str = "petroleum-gas";
+
+// Rounding noise below this is not reported as surplus.
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OilDemand {
+    pub heavy_oil: f64,
+    pub light_oil: f64,
+    pub petroleum_gas: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OilProcess {
+    pub recipe: String,
+    pub processer: Processer,
+    pub processer_num: u64,
+    pub craft_per_sec: f64,
+    /// Net flow per item, positive for products and negative for ingredients.
+    pub flows: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OilBalance {
+    pub refinery: OilProcess,
+    pub heavy_oil_cracking: OilProcess,
+    pub light_oil_cracking: OilProcess,
+    pub inputs: Vec<Throughput>,
+    pub surplus: Vec<Throughput>,
+}
+
+impl OilBalance {
+    pub fn processes(&self) -> Vec<&OilProcess> {
+        vec![
+            &self.refinery,
+            &self.heavy_oil_cracking,
+            &self.light_oil_cracking,
+        ]
+    }
+}
+
+/// Sizes refineries and cracking plants for `demand` with the fewest refineries.
+pub fn balance(
+    recipe_set: &RecipeSet,
+    processer_set: &ProcSet,
+    processer_choice: &ProcesserChoice,
+    demand: OilDemand,
+) -> Result<OilBalance> {
+    let refinery = find_recipe(recipe_set, "refinery", |r| {
+        r.has_result(HEAVY_OIL) && r.has_result(LIGHT_OIL) && r.has_result(PETROLEUM_GAS)
+    })?;
+    let heavy_cracking = find_recipe(recipe_set, "heavy oil cracking", |r| {
+        r.ingredient_num(HEAVY_OIL) > 0.0 && r.has_result(LIGHT_OIL)
+    })?;
+    let light_cracking = find_recipe(recipe_set, "light oil cracking", |r| {
+        r.ingredient_num(LIGHT_OIL) > 0.0 && r.has_result(PETROLEUM_GAS)
+    })?;
+
+    // Processers are chosen by the crafting power needed without productivity,
+    // the same estimate the solver uses.
+    let plain = crafts(refinery, heavy_cracking, light_cracking, (1.0, 1.0, 1.0), demand);
+    let procs = (
+        choose(processer_set, processer_choice, refinery, plain.0)?,
+        choose(processer_set, processer_choice, heavy_cracking, plain.1)?,
+        choose(processer_set, processer_choice, light_cracking, plain.2)?,
+    );
+    let prods = (
+        procs.0.productivity(),
+        procs.1.productivity(),
+        procs.2.productivity(),
+    );
+    let (x, y, z) = crafts(refinery, heavy_cracking, light_cracking, prods, demand);
+
+    let refinery = oil_process(refinery, procs.0, x);
+    let heavy_oil_cracking = oil_process(heavy_cracking, procs.1, y);
+    let light_oil_cracking = oil_process(light_cracking, procs.2, z);
+
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    for p in &[&refinery, &heavy_oil_cracking, &light_oil_cracking] {
+        for (n, f) in &p.flows {
+            *totals.entry(n.clone()).or_insert(0.0) += f;
+        }
+    }
+    *totals.entry(HEAVY_OIL.to_string()).or_insert(0.0) -= demand.heavy_oil;
+    *totals.entry(LIGHT_OIL.to_string()).or_insert(0.0) -= demand.light_oil;
+    *totals.entry(PETROLEUM_GAS.to_string()).or_insert(0.0) -= demand.petroleum_gas;
+
+    let inputs = totals
+        .iter()
+        .filter(|(_, &f)| f < -EPSILON)
+        .map(|(n, f)| Throughput::new(n.clone(), -f))
+        .collect();
+    let surplus = totals
+        .iter()
+        .filter(|(_, &f)| f > EPSILON)
+        .map(|(n, f)| Throughput::new(n.clone(), *f))
+        .collect();
+
+    Ok(OilBalance {
+        refinery,
+        heavy_oil_cracking,
+        light_oil_cracking,
+        inputs,
+        surplus,
+    })
+}
+
+fn find_recipe<'a, F: Fn(&Recipe) -> bool>(
+    recipe_set: &'a RecipeSet,
+    what: &str,
+    pred: F,
+) -> Result<&'a Recipe> {
+    recipe_set
+        .recipes()
+        .find(|r| pred(r))
+        .ok_or_else(|| format_err!("no {} recipe", what))
+}
+
+fn choose(
+    processer_set: &ProcSet,
+    processer_choice: &ProcesserChoice,
+    recipe: &Recipe,
+    craft_per_sec: f64,
+) -> Result<Processer> {
+    Ok(processer_set
+        .best_processer(
+            recipe.recipe_type(),
+            recipe.ingredients_count(),
+            recipe.is_material(),
+            recipe.cost() * craft_per_sec,
+            processer_choice,
+        )?
+        .clone())
+}
+
+/// Returns crafts per second of refinery, heavy oil cracking and light oil cracking.
+///
+/// The refinery rate is the smallest one meeting all three demands when every surplus
+/// is cracked down, cracking is then limited to what the demands actually need.
+fn crafts(
+    refinery: &Recipe,
+    heavy_cracking: &Recipe,
+    light_cracking: &Recipe,
+    prods: (f64, f64, f64),
+    demand: OilDemand,
+) -> (f64, f64, f64) {
+    let (pr, ph, pl) = prods;
+    let OilDemand {
+        heavy_oil: h,
+        light_oil: l,
+        petroleum_gas: g,
+    } = demand;
+
+    let rh = refinery.result_num(HEAVY_OIL) * pr;
+    let rl = refinery.result_num(LIGHT_OIL) * pr;
+    let rg = refinery.result_num(PETROLEUM_GAS) * pr;
+
+    let h_in = heavy_cracking.ingredient_num(HEAVY_OIL);
+    let h_out = heavy_cracking.result_num(LIGHT_OIL) * ph;
+    let l_in = light_cracking.ingredient_num(LIGHT_OIL);
+    let l_out = light_cracking.result_num(PETROLEUM_GAS) * pl;
+
+    // light oil per heavy oil, and petroleum gas per light oil by cracking
+    let k1 = h_out / h_in;
+    let k2 = l_out / l_in;
+
+    let x = [
+        0.0,
+        h / rh,
+        (l + k1 * h) / (rl + k1 * rh),
+        (g + k2 * (k1 * h + l)) / (rg + k2 * (rl + k1 * rh)),
+    ]
+    .iter()
+    .cloned()
+    .fold(0.0, f64::max);
+
+    let z = ((g - rg * x) / l_out).max(0.0);
+    let y = ((l + z * l_in - rl * x) / h_out).max(0.0);
+
+    (x, y, z)
+}
+
+fn oil_process(recipe: &Recipe, processer: Processer, craft_per_sec: f64) -> OilProcess {
+    let mut flows = BTreeMap::new();
+
+    for (n, c) in recipe.results() {
+        *flows.entry(n.clone()).or_insert(0.0) += c * processer.productivity() * craft_per_sec;
+    }
+    for (n, c) in recipe.ingredients() {
+        *flows.entry(n.clone()).or_insert(0.0) -= c * craft_per_sec;
+    }
+
+    let processer_num = (recipe.cost() * craft_per_sec / processer.speed()).ceil() as u64;
+
+    OilProcess {
+        recipe: recipe.name(),
+        processer,
+        processer_num,
+        craft_per_sec,
+        flows,
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+use crate::recipe::load_recipes;
+
+fn balance_for(demand: OilDemand) -> OilBalance {
+    balance(
+        &load_recipes("./data/oil").unwrap(),
+        &ProcSet::open_set().unwrap(),
+        &ProcesserChoice::new().productivity_module(false),
+        demand,
+    )
+    .unwrap()
+}
+
+fn amount(throughputs: &[Throughput], name: &str) -> f64 {
+    throughputs
+        .iter()
+        .find(|t| t.name() == name)
+        .map(Throughput::throughput)
+        .unwrap_or(0.0)
+}
+
+#[test]
+fn petroleum_gas_only_should_crack_everything() {
+    let balance = balance_for(OilDemand {
+        petroleum_gas: 1000.0,
+        ..OilDemand::default()
+    });
+
+    assert!(balance.surplus.is_empty(), "{:?}", balance.surplus);
+    assert!(balance.heavy_oil_cracking.craft_per_sec > 0.0);
+    assert!(balance.light_oil_cracking.craft_per_sec > 0.0);
+    assert!(amount(&balance.inputs, "crude-oil") > 0.0);
+}
+
+#[test]
+fn heavy_oil_only_should_leave_surplus() {
+    let balance = balance_for(OilDemand {
+        heavy_oil: 25.0,
+        ..OilDemand::default()
+    });
+
+    assert!((balance.refinery.craft_per_sec - 1.0).abs() < 1e-9);
+    assert_eq!(balance.heavy_oil_cracking.craft_per_sec, 0.0);
+    assert_eq!(balance.light_oil_cracking.craft_per_sec, 0.0);
+    assert!((amount(&balance.surplus, "light-oil") - 45.0).abs() < 1e-9);
+    assert!((amount(&balance.surplus, "petroleum-gas") - 55.0).abs() < 1e-9);
+    assert!((amount(&balance.inputs, "crude-oil") - 100.0).abs() < 1e-9);
+}
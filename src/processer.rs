@@ -1,5 +1,7 @@
 mod loader;
 
+use std::cmp::Reverse;
+
 use failure::{format_err, Error};
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -7,7 +9,10 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Processer {
     name: String,
+    machine: String,
     proc_type: String,
+    tier: u32,
+    burner_power: f64,
     productivity: f64,
     speed: f64,
     io: usize,
@@ -21,6 +26,10 @@ impl Processer {
         &self.name
     }
 
+    pub fn machine(&self) -> &str {
+        &self.machine
+    }
+
     pub fn productivity(&self) -> f64 {
         self.productivity
     }
@@ -29,6 +38,18 @@ impl Processer {
         self.speed
     }
 
+    /// Power drawn from burning fuel in kW.
+    pub fn burner_power(&self) -> f64 {
+        self.burner_power
+    }
+
+    pub fn with_productivity_bonus(&self, bonus: f64) -> Processer {
+        Processer {
+            productivity: self.productivity + bonus,
+            ..self.clone()
+        }
+    }
+
     pub fn use_prod_module(&self) -> bool {
         self.productivity_module > 0
     }
@@ -46,7 +67,7 @@ impl Processer {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProcSet {
     processers: Vec<Processer>,
 }
@@ -58,6 +79,11 @@ impl ProcSet {
         })
     }
 
+    /// Keeps only the processers matching `pred`.
+    pub fn retain<F: FnMut(&Processer) -> bool>(&mut self, pred: F) {
+        self.processers.retain(pred);
+    }
+
     pub fn best_processer(
         &self,
         proc_type: &str,
@@ -80,6 +106,9 @@ impl ProcSet {
                     || !(p.use_speed_module() && p.use_beacon())
             })
             .filter(|p| p.io > ingredients_count)
+            .filter(|p| processer_choice.max_tier.iter().all(|t| p.tier <= *t))
+            .filter(|p| processer_choice.machine.iter().all(|m| &p.machine == m))
+            .filter(|p| processer_choice.processer.iter().all(|n| &p.name == n))
             .collect();
 
         if candidates.is_empty() {
@@ -97,7 +126,8 @@ impl ProcSet {
             .cloned()
             .collect();
 
-        candidates.sort_by_key(|p| p.cost());
+        // Among equal costs, the newest machine unlocked is preferred.
+        candidates.sort_by_key(|p| (p.cost(), Reverse(p.tier)));
         candidates.sort_by_key(|p| (crafting_power / p.speed).ceil() as usize);
 
         let max_speed = candidates
@@ -124,6 +154,9 @@ pub struct ProcesserChoice {
     allow_speed_module: bool,
     allow_productivity_module: bool,
     allow_speed_only_beacon: bool,
+    max_tier: Option<u32>,
+    machine: Option<String>,
+    processer: Option<String>,
 }
 
 impl ProcesserChoice {
@@ -158,6 +191,30 @@ impl ProcesserChoice {
             ..self.clone()
         }
     }
+
+    /// Allows only machines up to `tier`, like 2 for assembling machine 2.
+    pub fn max_tier(&self, tier: Option<u32>) -> ProcesserChoice {
+        ProcesserChoice {
+            max_tier: tier,
+            ..self.clone()
+        }
+    }
+
+    /// Allows only variants of the machine `name`.
+    pub fn machine(&self, name: &str) -> ProcesserChoice {
+        ProcesserChoice {
+            machine: Some(name.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Allows only the processer variant `name`, like `assembler-p4-b8`.
+    pub fn processer(&self, name: &str) -> ProcesserChoice {
+        ProcesserChoice {
+            processer: Some(name.to_string()),
+            ..self.clone()
+        }
+    }
 }
 
 impl Default for ProcesserChoice {
@@ -167,6 +224,9 @@ impl Default for ProcesserChoice {
             allow_speed_module: true,
             allow_productivity_module: true,
             allow_speed_only_beacon: true,
+            max_tier: None,
+            machine: None,
+            processer: None,
         }
     }
 }
@@ -31,7 +31,12 @@ pub fn load() -> Result<Vec<Processer>, Error> {
                 }
 
                 let p = build_proc(&t.name, t.base_speed, m, c);
-                res.push(p);
+                res.push(Processer {
+                    proc_type: t.category().to_string(),
+                    tier: t.tier,
+                    burner_power: t.burner_power,
+                    ..p
+                });
             }
         }
     }
@@ -71,7 +76,10 @@ fn build_proc(
 
     Processer {
         name,
+        machine: base_name.to_string(),
         proc_type: base_name.to_string(),
+        tier: 1,
+        burner_power: 0.0,
         productivity,
         speed,
         io: conf.io,
@@ -84,11 +92,29 @@ fn build_proc(
 #[derive(Debug, Deserialize)]
 struct ProcType {
     name: String,
+    /// Recipe type served by this machine, the name when omitted.
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default = "default_tier")]
+    tier: u32,
+    /// Power drawn from burning fuel in kW, zero for electric machines.
+    #[serde(default)]
+    burner_power: f64,
     base_speed: f64,
     max_modules: usize,
     configulations: Vec<Configulation>,
 }
 
+impl ProcType {
+    fn category(&self) -> &str {
+        self.category.as_ref().unwrap_or(&self.name)
+    }
+}
+
+fn default_tier() -> u32 {
+    1
+}
+
 #[derive(Debug, Deserialize)]
 struct Configulation {
     beacon: usize,
@@ -11,14 +11,21 @@ use crate::near_name::NameSet;
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Recipe types affected by mining productivity research.
+pub const MINING_TYPES: [&str; 2] = ["mining-drill", "pumpjack"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Recipe {
+    name: Option<String>,
     #[serde(rename = "type")]
     recipe_type: String,
     cost: f64,
     #[serde(default)]
     material: bool,
+    /// Seconds per result the processer is busy besides crafting, not sped up by modules.
+    #[serde(default)]
+    overhead: f64,
     results: BTreeMap<String, f64>,
     ingredients: BTreeMap<String, f64>,
     // metadata
@@ -27,6 +34,18 @@ pub struct Recipe {
 }
 
 impl Recipe {
+    pub fn name(&self) -> String {
+        match self.name {
+            Some(ref n) => n.clone(),
+            None => self
+                .results
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join("+"),
+        }
+    }
+
     pub fn recipe_type(&self) -> &str {
         &self.recipe_type
     }
@@ -39,6 +58,26 @@ impl Recipe {
         self.material
     }
 
+    pub fn overhead(&self) -> f64 {
+        self.overhead
+    }
+
+    pub fn is_mining(&self) -> bool {
+        MINING_TYPES.contains(&self.recipe_type.as_str())
+    }
+
+    /// Returns a copy of the recipe with every result multiplied by `mult`.
+    pub fn with_result_mult(&self, mult: f64) -> Recipe {
+        Recipe {
+            results: self
+                .results
+                .iter()
+                .map(|(n, c)| (n.clone(), c * mult))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
     pub fn has_result(&self, result: &str) -> bool {
         self.results.get(result).is_some()
     }
@@ -50,6 +89,13 @@ impl Recipe {
         }
     }
 
+    pub fn ingredient_num(&self, ingredient: &str) -> f64 {
+        match self.ingredients.get(ingredient) {
+            Some(&n) => n,
+            None => 0.0,
+        }
+    }
+
     pub fn results(&self) -> impl Iterator<Item = (&String, &f64)> {
         self.results.iter()
     }
@@ -75,7 +121,7 @@ impl Recipe {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RecipeSet {
     recipes: Vec<Recipe>,
 }
@@ -91,6 +137,11 @@ impl RecipeSet {
         self.recipes.append(&mut recipes);
     }
 
+    /// Keeps only the recipes matching `pred`.
+    pub fn retain<F: FnMut(&Recipe) -> bool>(&mut self, pred: F) {
+        self.recipes.retain(pred);
+    }
+
     pub fn find_recipes(&self, result: &str) -> Vec<&Recipe> {
         self.recipes
             .as_slice()
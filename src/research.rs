@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use failure::{format_err, Error};
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A technology to research: `units` research units, each consuming `ingredients`
+/// and taking `unit_time` seconds in a lab of speed 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Research {
+    pub units: f64,
+    pub ingredients: BTreeMap<String, f64>,
+    pub unit_time: f64,
+}
+
+/// Lab research bonuses, both in percent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Labs {
+    pub speed: f64,
+    pub productivity: f64,
+}
+
+impl Research {
+    /// Parses an ingredient written as `NAME=COUNT`, e.g. `automation-science-pack=1`.
+    pub fn parse_ingredient(s: &str) -> Result<(String, f64)> {
+        let mut parts = s.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let count = parts
+            .next()
+            .ok_or_else(|| format_err!("pack must be like NAME=COUNT: {}", s))?;
+        if name.is_empty() {
+            return Err(format_err!("pack must be like NAME=COUNT: {}", s));
+        }
+
+        Ok((name.to_string(), count.trim().parse()?))
+    }
+
+    /// Lab seconds to finish the research, as one lab would take alone.
+    pub fn lab_seconds(&self, labs: Labs) -> f64 {
+        self.crafted_units(labs) * self.unit_time / (1.0 + labs.speed / 100.0)
+    }
+
+    /// Labs needed to finish the research within `seconds`.
+    pub fn labs_for(&self, labs: Labs, seconds: f64) -> Result<u64> {
+        self.validate()?;
+        if seconds <= 0.0 || seconds.is_nan() {
+            return Err(format_err!("time must be positive: {}", seconds));
+        }
+
+        Ok((self.lab_seconds(labs) / seconds).ceil() as u64)
+    }
+
+    /// Seconds to finish the research with `lab_num` labs.
+    pub fn time_for(&self, labs: Labs, lab_num: u64) -> Result<f64> {
+        self.validate()?;
+        if lab_num == 0 {
+            return Err(format_err!("labs must be positive: {}", lab_num));
+        }
+
+        Ok(self.lab_seconds(labs) / lab_num as f64)
+    }
+
+    /// Science packs consumed per second when the research takes `seconds`.
+    pub fn pack_rates(&self, labs: Labs, seconds: f64) -> Vec<(String, f64)> {
+        let units_per_sec = self.crafted_units(labs) / seconds;
+        self.ingredients
+            .iter()
+            .map(|(n, c)| (n.clone(), c * units_per_sec))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.units <= 0.0 || self.units.is_nan() {
+            return Err(format_err!("units must be positive: {}", self.units));
+        }
+        if self.unit_time <= 0.0 || self.unit_time.is_nan() {
+            return Err(format_err!(
+                "unit time must be positive: {}",
+                self.unit_time
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Units actually consumed, lab productivity adds the rest for free.
+    fn crafted_units(&self, labs: Labs) -> f64 {
+        self.units / (1.0 + labs.productivity / 100.0)
+    }
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+fn research() -> Research {
+    let mut ingredients = BTreeMap::new();
+    ingredients.insert("automation-science-pack".to_string(), 1.0);
+    ingredients.insert("logistic-science-pack".to_string(), 1.0);
+
+    Research {
+        units: 300.0,
+        ingredients,
+        unit_time: 30.0,
+    }
+}
+
+#[test]
+fn parse_ingredient() {
+    assert_eq!(
+        Research::parse_ingredient("chemical-science-pack=2").unwrap(),
+        ("chemical-science-pack".to_string(), 2.0)
+    );
+
+    assert!(Research::parse_ingredient("chemical-science-pack").is_err());
+    assert!(Research::parse_ingredient("=2").is_err());
+}
+
+#[test]
+fn labs_and_time_should_include_bonuses() {
+    let research = research();
+    let labs = Labs {
+        speed: 50.0,
+        productivity: 20.0,
+    };
+
+    // 300 units / 1.2 * 30 s / 1.5 = 5000 lab seconds.
+    assert!((research.lab_seconds(labs) - 5000.0).abs() < 1e-9);
+    assert_eq!(research.labs_for(labs, 600.0).unwrap(), 9);
+    assert!((research.time_for(labs, 10).unwrap() - 500.0).abs() < 1e-9);
+}
+
+#[test]
+fn labs_and_time_should_reject_non_positive_values() {
+    let labs = Labs::default();
+
+    assert!(research().labs_for(labs, 0.0).is_err());
+    assert!(research().labs_for(labs, -60.0).is_err());
+    assert!(research().time_for(labs, 0).is_err());
+
+    let mut research = research();
+    research.units = -300.0;
+    assert!(research.labs_for(labs, 600.0).is_err());
+
+    research.units = 300.0;
+    research.unit_time = 0.0;
+    assert!(research.time_for(labs, 10).is_err());
+}
+
+#[test]
+fn pack_rates_should_skip_productivity_units() {
+    let labs = Labs {
+        speed: 0.0,
+        productivity: 50.0,
+    };
+
+    let rates = research().pack_rates(labs, 100.0);
+
+    assert_eq!(rates.len(), 2);
+    assert_eq!(rates[0].0, "automation-science-pack");
+    assert!((rates[0].1 - 2.0).abs() < 1e-9);
+    assert!((rates[1].1 - 2.0).abs() < 1e-9);
+}
@@ -1,10 +1,129 @@
+use crate::oil::OilBalance;
 use crate::processer::Processer;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Solution {
+    pub graph: ProductionGraph,
     pub trees: Vec<ProcessingTree>,
     pub sources: Vec<Throughput>,
     pub missings: Vec<Missing>,
+    /// Demand of limited sources neither imported nor produced.
+    pub shortfalls: Vec<Throughput>,
+    pub auto_merged: Vec<AutoMerged>,
+    pub oil: Option<OilBalance>,
+    /// Fuel burnt by burner processers.
+    pub fuel: Option<Throughput>,
+    /// How the targets were scaled to fit limited supplies and pinned processes.
+    pub fit: Option<Fit>,
+}
+
+impl Solution {
+    pub fn summary(&self) -> Vec<ItemSummary> {
+        let mut summary: Vec<ItemSummary> = self
+            .graph
+            .recipes
+            .iter()
+            .map(|r| ItemSummary {
+                throughput: r.output.clone(),
+                recipe: r.recipe.clone(),
+                processer: r.processer.clone(),
+                processer_num: r.processer_num,
+                craft_per_sec: r.craft_per_sec,
+            })
+            .collect();
+
+        summary.sort_by(|l, r| {
+            l.throughput
+                .name()
+                .cmp(r.throughput.name())
+                .then_with(|| l.recipe.cmp(&r.recipe))
+        });
+        summary
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductionGraph {
+    pub items: Vec<ItemNode>,
+    pub recipes: Vec<RecipeNode>,
+}
+
+impl ProductionGraph {
+    pub fn producer(&self, name: &str) -> Option<&RecipeNode> {
+        self.recipes.iter().find(|r| r.output.name() == name)
+    }
+
+    pub fn consumers(&self, name: &str) -> Vec<&RecipeNode> {
+        self.recipes
+            .iter()
+            .filter(|r| r.inputs.iter().any(|i| i.name() == name))
+            .collect()
+    }
+
+    pub fn flows(&self) -> Vec<FlowEdge> {
+        let mut flows = Vec::new();
+
+        for (idx, r) in self.recipes.iter().enumerate() {
+            for i in &r.inputs {
+                flows.push(FlowEdge {
+                    from: NodeRef::Item(i.name().to_string()),
+                    to: NodeRef::Recipe(idx),
+                    throughput: i.clone(),
+                });
+            }
+            flows.push(FlowEdge {
+                from: NodeRef::Recipe(idx),
+                to: NodeRef::Item(r.output.name().to_string()),
+                throughput: r.output.clone(),
+            });
+        }
+
+        flows
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemNode {
+    pub throughput: Throughput,
+    pub source: f64,
+    pub target: f64,
+    pub missing: bool,
+    /// Demand above the source limit which is not produced.
+    pub shortfall: f64,
+}
+
+impl ItemNode {
+    pub fn is_source(&self) -> bool {
+        self.source > 0.0
+    }
+
+    pub fn is_target(&self) -> bool {
+        self.target > 0.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeNode {
+    pub recipe: String,
+    pub output: Throughput,
+    pub inputs: Vec<Throughput>,
+    pub processer: Processer,
+    pub processer_num: u64,
+    pub machines: f64,
+    pub craft_per_sec: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowEdge {
+    pub from: NodeRef,
+    pub to: NodeRef,
+    pub throughput: Throughput,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeRef {
+    Item(String),
+    Recipe(usize),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -15,12 +134,22 @@ pub struct ProcessingTree {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Process {
     pub throughput: Throughput,
+    pub recipe: String,
     pub processer: Processer,
     pub processer_num: u64,
     pub craft_per_sec: f64,
     pub sources: Vec<Source>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemSummary {
+    pub throughput: Throughput,
+    pub recipe: String,
+    pub processer: Processer,
+    pub processer_num: u64,
+    pub craft_per_sec: f64,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Source {
     Process(Process),
@@ -32,6 +161,8 @@ pub enum Source {
 pub enum Throughput {
     Item(String, f64),
     Liquid(String, f64),
+    /// Electricity or heat, one per second is one MW.
+    Power(String, f64),
 }
 
 impl Throughput {
@@ -44,10 +175,16 @@ impl Throughput {
             "petroleum-gas",
             "lubricant",
             "sulfuric-acid",
+            "steam",
+            "high-temperature-steam",
         ];
 
+        let powers = ["electricity", "heat"];
+
         if liquids.contains(&name.as_str()) {
             Throughput::Liquid(name, throughput)
+        } else if powers.contains(&name.as_str()) {
+            Throughput::Power(name, throughput)
         } else {
             Throughput::Item(name, throughput)
         }
@@ -57,6 +194,7 @@ impl Throughput {
         match self {
             Throughput::Item(n, _) => n,
             Throughput::Liquid(n, _) => n,
+            Throughput::Power(n, _) => n,
         }
     }
 
@@ -64,6 +202,77 @@ impl Throughput {
         match self {
             Throughput::Item(_, t) => *t,
             Throughput::Liquid(_, t) => *t,
+            Throughput::Power(_, t) => *t,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    /// Multiplier applied to the targets.
+    pub mult: f64,
+    /// The supply or pinned process used up first.
+    pub binding: String,
+    pub supplies: Vec<SupplyUsage>,
+    pub pins: Vec<PinUsage>,
+}
+
+impl Fit {
+    /// Load of the most used supply or pinned process, 1.0 when fully used.
+    pub fn max_load(&self) -> f64 {
+        self.supplies
+            .iter()
+            .map(SupplyUsage::load)
+            .chain(self.pins.iter().map(PinUsage::load))
+            .fold(0.0, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupplyUsage {
+    pub name: String,
+    pub used: f64,
+    pub supplied: f64,
+}
+
+impl SupplyUsage {
+    pub fn load(&self) -> f64 {
+        self.used / self.supplied
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinUsage {
+    pub name: String,
+    pub processer: String,
+    pub count: u64,
+    /// Machines needed, at most `count`.
+    pub machines: f64,
+}
+
+impl PinUsage {
+    pub fn load(&self) -> f64 {
+        self.machines / self.count as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoMerged {
+    pub name: String,
+    pub reason: MergeReason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeReason {
+    Consumers(usize),
+    Belts(f64),
+}
+
+impl std::fmt::Display for MergeReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MergeReason::Consumers(n) => write!(f, "consumed by {} processes", n),
+            MergeReason::Belts(b) => write!(f, "demand is {:.1} B", b),
         }
     }
 }
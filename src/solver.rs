@@ -1,29 +1,73 @@
 use std::cmp::Ordering;
 use std::collections::btree_map::Iter;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
-use failure::Error;
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
 
+use crate::consts::BELT_THROUGHPUT;
+use crate::fuel::FuelDict;
+use crate::oil::{self, OilBalance, OilDemand, HEAVY_OIL, LIGHT_OIL, PETROLEUM_GAS};
 use crate::recipe::RecipeSet;
 use crate::solution::*;
-use crate::target::{Flow, TargetSettings};
+use crate::target::{Flow, Pin, ProcesserOverride, TargetSettings};
+
+#[cfg(test)]
+mod tests;
 
 pub use crate::processer::{ProcSet, ProcesserChoice};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+const MAX_FUEL_ITERATIONS: usize = 100;
+const FUEL_TOLERANCE: f64 = 1e-9;
+const MAX_FIT_ITERATIONS: usize = 100;
+const FIT_TOLERANCE: f64 = 1e-9;
+/// Machine counts this close above a whole number are not rounded up.
+const MACHINE_TOLERANCE: f64 = 1e-6;
+const SHORTFALL_TOLERANCE: f64 = 1e-9;
+
 #[derive(Debug)]
 pub struct Solver {
     targets: ItemThroughputs,
+    supplies: Vec<Flow>,
     recipe_set: RecipeSet,
     sources: HashSet<String>,
+    source_limits: BTreeMap<String, f64>,
+    no_remainder: bool,
     merged: HashSet<String>,
     all_merged: bool,
     never_merged: HashSet<String>,
     processer_set: ProcSet,
     processer_choice: ProcesserChoice,
-    source_throughputs: ItemThroughputs,
-    missings: BTreeSet<String>,
+    processer_overrides: BTreeMap<String, ProcesserOverride>,
+    pinned: BTreeMap<String, Pin>,
+    auto_merge: Option<AutoMerge>,
+    balance_oil: bool,
+    mining_productivity: f64,
+    fuel: Option<String>,
+    fuel_dict: FuelDict,
+    oil_recipe_set: RecipeSet,
+}
+
+/// Thresholds for merging shared intermediates automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutoMerge {
+    /// Merge items consumed by more than this number of processes.
+    pub consumers: usize,
+    /// Merge items whose total demand is above this number of belts. Fluids are piped,
+    /// so they are never merged by this threshold.
+    pub belts: f64,
+}
+
+impl Default for AutoMerge {
+    fn default() -> Self {
+        AutoMerge {
+            consumers: 1,
+            belts: 1.0,
+        }
+    }
 }
 
 impl Solver {
@@ -31,8 +75,8 @@ impl Solver {
         recipe_set: RecipeSet,
         target_settings: &TargetSettings,
         processer_set: ProcSet,
-        processer_choice: ProcesserChoice,
     ) -> Solver {
+        let settings = target_settings.solver_settings();
         let mut targets = ItemThroughputs::new();
 
         for t in target_settings.targets() {
@@ -41,152 +85,664 @@ impl Solver {
 
         Solver {
             targets,
+            supplies: target_settings.supplies().to_vec(),
             recipe_set,
+            // Limited sources are solved like other demands to import them up to the limit.
             sources: target_settings
                 .sources()
                 .iter()
+                .filter(|s| !target_settings.source_limits().contains_key(*s))
                 .map(ToOwned::to_owned)
                 .collect(),
+            source_limits: target_settings.source_limits().clone(),
+            no_remainder: settings.no_remainder,
             merged: target_settings
                 .merged()
                 .iter()
                 .map(ToOwned::to_owned)
                 .collect(),
-            all_merged: false,
-            never_merged: HashSet::new(),
+            all_merged: settings.all_merged,
+            never_merged: settings.never_merged.iter().cloned().collect(),
             processer_set,
-            processer_choice,
-            source_throughputs: ItemThroughputs::new(),
-            missings: BTreeSet::new(),
+            processer_choice: settings.processer_choice(),
+            processer_overrides: settings.overrides.clone(),
+            pinned: settings.pinned.clone(),
+            auto_merge: settings.auto_merge.clone(),
+            balance_oil: settings.balance_oil,
+            mining_productivity: settings.mining_productivity / 100.0,
+            fuel: settings.fuel.clone(),
+            fuel_dict: FuelDict::default(),
+            oil_recipe_set: RecipeSet::new(),
         }
     }
 
-    pub fn solve(&mut self) -> Result<Solution> {
-        let mut trees = Vec::new();
-        while let Some(t) = self.next_target() {
-            if let Some(process) = self.solve_one(t)? {
-                trees.push(ProcessingTree { process });
+    /// Sets fuel values used to burn the fuel setting in burner processers.
+    pub fn fuel_dict(&mut self, fuel_dict: FuelDict) {
+        self.fuel_dict = fuel_dict;
+    }
+
+    /// Sets the refinery and cracking recipes oil products are balanced with.
+    ///
+    /// They are kept out of the recipe set, as solving each oil product on its own would
+    /// build a refinery per product and drop the byproducts.
+    pub fn oil_recipe_set(&mut self, oil_recipe_set: RecipeSet) {
+        self.oil_recipe_set = oil_recipe_set;
+    }
+
+    pub fn solve(&self) -> Result<Solution> {
+        let (targets, mult) = self.fit_targets()?;
+        let (graph, fuel) = self.solve_fueled_graph(&targets)?;
+        let (sources, oil) = self.solve_sources(&graph)?;
+        let fit = mult.map(|m| self.fit(&graph, &sources, m));
+
+        // Merges picked for this solution only, so solving again starts from the settings.
+        let mut merged = self.merged.clone();
+        if let Some(ref f) = fuel {
+            // Fuel made on site gets its own tree instead of being nested in none.
+            if graph.producer(f.name()).is_some() {
+                merged.insert(f.name().to_string());
             }
         }
+        let auto_merged = self.select_auto_merged(&graph);
+        for m in &auto_merged {
+            merged.insert(m.name.clone());
+        }
+        let trees = self.derive_trees(&graph, &merged);
 
-        let missings = self
-            .missings
+        let missings = graph
+            .items
             .iter()
-            .map(|n| Missing {
-                name: n.to_string(),
-                candidates: self.recipe_set.find_did_you_mean(n),
+            .filter(|i| i.missing)
+            .map(|i| Missing {
+                name: i.throughput.name().to_string(),
+                candidates: self.recipe_set.find_did_you_mean(i.throughput.name()),
             })
             .collect();
 
+        let shortfalls = graph
+            .items
+            .iter()
+            .filter(|i| i.shortfall > 0.0)
+            .map(|i| Throughput::new(i.throughput.name().to_string(), i.shortfall))
+            .collect();
+
         Ok(Solution {
+            graph,
             trees,
-            sources: self
-                .source_throughputs
-                .iter()
-                .map(|(n, t)| Throughput::new(n.clone(), *t))
-                .collect(),
+            sources,
             missings,
+            shortfalls,
+            auto_merged,
+            oil,
+            fuel,
+            fit,
         })
     }
 
-    pub fn all_merged(&mut self, flag: bool) {
-        self.all_merged = flag;
+    /// Returns the targets scaled to the largest multiplier the supplies and pinned
+    /// processes can feed, together with the multiplier.
+    ///
+    /// Demand grows with the targets, but fuel and oil balancing make it slightly
+    /// nonlinear, so the multiplier is refined until it settles.
+    ///
+    /// A supply is how much of an item there is while a source limit imports part of
+    /// an item and produces the rest, so an item may not have both.
+    fn fit_targets(&self) -> Result<(ItemThroughputs, Option<f64>)> {
+        let mut targets = self.targets.clone();
+        if self.supplies.is_empty() && self.pinned.is_empty() {
+            return Ok((targets, None));
+        }
+        if let Some(s) = self
+            .supplies
+            .iter()
+            .find(|s| self.source_limits.contains_key(&s.name))
+        {
+            return Err(format_err!(
+                "{} has both a supply and a source limit",
+                s.name
+            ));
+        }
+
+        let mut mult = 1.0;
+        for _ in 0..MAX_FIT_ITERATIONS {
+            let (graph, _) = self.solve_fueled_graph(&targets)?;
+            let (sources, _) = self.solve_sources(&graph)?;
+            let load = self.fit(&graph, &sources, mult).max_load();
+            if load <= 0.0 || !load.is_finite() {
+                return Err(format_err!(
+                    "no supply or pinned process is used by the targets"
+                ));
+            }
+
+            let ratio = 1.0 / load;
+            targets.multiply(ratio);
+            mult *= ratio;
+            if (ratio - 1.0).abs() <= FIT_TOLERANCE {
+                return Ok((targets, Some(mult)));
+            }
+        }
+
+        Err(format_err!(
+            "targets do not settle within the supplies and pinned processes"
+        ))
     }
 
-    pub fn never_merged<S: AsRef<str>, I: Iterator<Item = S>>(&mut self, names: I) {
-        self.never_merged = names.map(|s| s.as_ref().to_string()).collect();
+    fn fit(&self, graph: &ProductionGraph, sources: &[Throughput], mult: f64) -> Fit {
+        let supplies = self.supply_usages(sources);
+        let pins = self.pin_usages(graph);
+        let binding = supplies
+            .iter()
+            .map(|u| (&u.name, u.load()))
+            .chain(pins.iter().map(|p| (&p.name, p.load())))
+            .filter(|(_, load)| *load > 0.0)
+            .max_by(|l, r| l.1.partial_cmp(&r.1).unwrap_or(Ordering::Equal))
+            .map(|(n, _)| n.clone())
+            .unwrap_or_default();
+
+        Fit {
+            mult,
+            binding,
+            supplies,
+            pins,
+        }
     }
 
-    fn next_target(&mut self) -> Option<Flow> {
-        if let Some(name) = self.targets.names().into_iter().min_by(|l, r| {
-            let ml = self.merged.contains(l);
-            let mr = self.merged.contains(r);
-            if ml && !mr {
-                return Ordering::Greater;
+    fn pin_usages(&self, graph: &ProductionGraph) -> Vec<PinUsage> {
+        self.pinned
+            .iter()
+            .map(|(n, pin)| PinUsage {
+                name: n.clone(),
+                processer: pin.processer.clone(),
+                count: pin.count,
+                machines: graph.producer(n).map(|r| r.machines).unwrap_or(0.0),
+            })
+            .collect()
+    }
+
+    fn supply_usages(&self, sources: &[Throughput]) -> Vec<SupplyUsage> {
+        self.supplies
+            .iter()
+            .map(|s| SupplyUsage {
+                name: s.name.clone(),
+                used: sources
+                    .iter()
+                    .find(|t| t.name() == s.name)
+                    .map(Throughput::throughput)
+                    .unwrap_or(0.0),
+                supplied: s.throughput,
+            })
+            .collect()
+    }
+
+    /// Collects the sources of the graph, with balanced oil products replaced by the
+    /// inputs of the oil block.
+    fn solve_sources(
+        &self,
+        graph: &ProductionGraph,
+    ) -> Result<(Vec<Throughput>, Option<OilBalance>)> {
+        let mut sources: Vec<Throughput> = graph
+            .items
+            .iter()
+            .filter(|i| i.is_source())
+            .map(|i| Throughput::new(i.throughput.name().to_string(), i.source))
+            .collect();
+
+        let oil = self.solve_oil(&mut sources)?;
+        Ok((sources, oil))
+    }
+
+    /// Replaces oil product sources with the crude oil and water of a balanced oil block.
+    fn solve_oil(&self, sources: &mut Vec<Throughput>) -> Result<Option<OilBalance>> {
+        let amount = |name: &str| {
+            sources
+                .iter()
+                .find(|s| s.name() == name && self.is_balanced_oil(name))
+                .map(Throughput::throughput)
+                .unwrap_or(0.0)
+        };
+        let demand = OilDemand {
+            heavy_oil: amount(HEAVY_OIL),
+            light_oil: amount(LIGHT_OIL),
+            petroleum_gas: amount(PETROLEUM_GAS),
+        };
+
+        if demand == OilDemand::default() {
+            return Ok(None);
+        }
+
+        let balance = oil::balance(
+            &self.oil_recipe_set,
+            &self.processer_set,
+            &self.processer_choice,
+            demand,
+        )?;
+
+        let mut throughputs = ItemThroughputs::new();
+        for s in sources.iter().chain(balance.inputs.iter()) {
+            if self.is_balanced_oil(s.name()) {
+                continue;
             }
-            if !ml && mr {
-                return Ordering::Less;
+            throughputs.add(Flow {
+                name: s.name().to_string(),
+                throughput: s.throughput(),
+            });
+        }
+        *sources = throughputs
+            .iter()
+            .map(|(n, t)| Throughput::new(n.clone(), *t))
+            .collect();
+
+        Ok(Some(balance))
+    }
+
+    fn select_auto_merged(&self, graph: &ProductionGraph) -> Vec<AutoMerged> {
+        let auto_merge = match self.auto_merge {
+            Some(ref a) => a,
+            None => return Vec::new(),
+        };
+
+        let mut res = Vec::new();
+
+        for r in &graph.recipes {
+            let name = r.output.name();
+            if self.never_merged.contains(name) || self.is_merged(&self.merged, name) {
+                continue;
             }
 
-            self.recipe_set.compare(l, r, &self.sources)
-        }) {
-            return Some(self.targets.take(name));
+            let consumers = graph.consumers(name).len();
+            let reason = if consumers > auto_merge.consumers {
+                MergeReason::Consumers(consumers)
+            } else {
+                match r.output {
+                    Throughput::Item(_, t) if t / BELT_THROUGHPUT > auto_merge.belts => {
+                        MergeReason::Belts(t / BELT_THROUGHPUT)
+                    }
+                    _ => continue,
+                }
+            };
+
+            res.push(AutoMerged {
+                name: name.to_string(),
+                reason,
+            });
+        }
+
+        res.sort_by(|l, r| l.name.cmp(&r.name));
+        res
+    }
+
+    /// Solves the graph with the fuel burnt by its burner processers added as demand.
+    ///
+    /// Fuel may itself be made by burner processers, so the demand is solved again
+    /// until it settles.
+    fn solve_fueled_graph(
+        &self,
+        targets: &ItemThroughputs,
+    ) -> Result<(ProductionGraph, Option<Throughput>)> {
+        let fuel = match self.fuel {
+            Some(ref fuel) => fuel,
+            None => return Ok((self.solve_graph(targets, None)?, None)),
+        };
+        let fuel_value = self
+            .fuel_dict
+            .get(fuel)
+            .ok_or_else(|| format_err!("unknown fuel value: {}", fuel))?;
+
+        let mut demand = 0.0;
+        for _ in 0..MAX_FUEL_ITERATIONS {
+            let graph = self.solve_graph(
+                targets,
+                Some(&Flow {
+                    name: fuel.clone(),
+                    throughput: demand,
+                }),
+            )?;
+
+            let burner_power: f64 = graph
+                .recipes
+                .iter()
+                .map(|r| r.machines * r.processer.burner_power())
+                .sum();
+            // kW divided by kJ per item
+            let next = burner_power / (fuel_value * 1000.0);
+
+            if (next - demand).abs() <= FUEL_TOLERANCE {
+                let fuel = if demand > 0.0 {
+                    Some(Throughput::new(fuel.clone(), demand))
+                } else {
+                    None
+                };
+                return Ok((graph, fuel));
+            }
+            demand = next;
+        }
+
+        Err(format_err!("fuel demand of {} does not settle", fuel))
+    }
+
+    /// Builds the production graph.
+    ///
+    /// Items are solved deepest first, so every consumer of an item has added its demand
+    /// before the item itself is solved, and each recipe gets a single machine count.
+    fn solve_graph(
+        &self,
+        targets: &ItemThroughputs,
+        fuel: Option<&Flow>,
+    ) -> Result<ProductionGraph> {
+        let mut demands = targets.clone();
+        let mut items: BTreeMap<String, ItemAcc> = BTreeMap::new();
+        let mut recipes = Vec::new();
+
+        for (n, t) in targets.iter() {
+            let acc = items.entry(n.clone()).or_default();
+            acc.total += t;
+            acc.target += t;
+        }
+
+        if let Some(f) = fuel.filter(|f| f.throughput > 0.0) {
+            let acc = items.entry(f.name.clone()).or_default();
+            acc.total += f.throughput;
+            if self.sources.contains(&f.name) {
+                acc.source += f.throughput;
+            } else {
+                demands.add(f.clone());
+            }
+        }
+
+        while let Some(t) = self.next_demand(&mut demands) {
+            let t = match self.source_limits.get(&t.name) {
+                Some(&limit) => {
+                    let acc = items.entry(t.name.clone()).or_default();
+                    let imported = t.throughput.min(limit);
+                    acc.source += imported;
+
+                    let rest = t.throughput - imported;
+                    if rest <= SHORTFALL_TOLERANCE {
+                        continue;
+                    }
+                    if self.no_remainder || self.recipe_set.find_recipes(&t.name).is_empty() {
+                        acc.shortfall += rest;
+                        continue;
+                    }
+                    Flow {
+                        name: t.name,
+                        throughput: rest,
+                    }
+                }
+                None => t,
+            };
+
+            if self.is_balanced_oil(&t.name) {
+                items.entry(t.name).or_default().source += t.throughput;
+                continue;
+            }
+
+            match self.solve_recipe(&t)? {
+                Some(node) => {
+                    for i in &node.inputs {
+                        let acc = items.entry(i.name().to_string()).or_default();
+                        acc.total += i.throughput();
+
+                        if self.sources.contains(i.name()) {
+                            acc.source += i.throughput();
+                        } else {
+                            demands.add(Flow {
+                                name: i.name().to_string(),
+                                throughput: i.throughput(),
+                            });
+                        }
+                    }
+                    recipes.push(node);
+                }
+                None => {
+                    let acc = items.entry(t.name).or_default();
+                    acc.source += t.throughput;
+                    acc.missing = true;
+                }
+            }
+        }
+
+        let items = items
+            .into_iter()
+            .map(|(n, acc)| ItemNode {
+                throughput: Throughput::new(n, acc.total),
+                source: acc.source,
+                target: acc.target,
+                missing: acc.missing,
+                shortfall: acc.shortfall,
+            })
+            .collect();
+
+        Ok(ProductionGraph { items, recipes })
+    }
+
+    fn next_demand(&self, demands: &mut ItemThroughputs) -> Option<Flow> {
+        if let Some(name) = demands
+            .names()
+            .into_iter()
+            .min_by(|l, r| self.recipe_set.compare(l, r, &self.sources))
+        {
+            return Some(demands.take(name));
         }
 
         None
     }
 
-    fn solve_one(&mut self, t: Flow) -> Result<Option<Process>> {
+    fn solve_recipe(&self, t: &Flow) -> Result<Option<RecipeNode>> {
         let recipes = self.recipe_set.find_recipes(&t.name);
         if recipes.is_empty() {
-            self.missings.insert(t.name.clone());
-            self.source_throughputs.add(t.clone());
             return Ok(None);
         }
 
         let r = recipes[0];
         let result_num = r.result_num(&t.name);
-        let processer = self
+        let mut processer = self
             .processer_set
             .best_processer(
                 r.recipe_type(),
                 r.ingredients_count(),
                 r.is_material(),
                 r.cost() * t.throughput / result_num,
-                &self.processer_choice,
-            )?
+                &self.processer_choice_for(&r.name(), &t.name),
+            )
+            .map_err(|e| format_err!("{}: {}", t.name, e))?
             .clone();
+        if r.is_mining() {
+            processer = processer.with_productivity_bonus(self.mining_productivity);
+        }
         let craft_throughput = t.throughput / (processer.productivity() * result_num);
-        let unit_count = (r.cost() * craft_throughput / processer.speed()).ceil() as u64;
-
-        let ingredients: Vec<(String, f64)> =
-            r.ingredients().map(|(n, c)| (n.clone(), *c)).collect();
+        let machines =
+            r.cost() * craft_throughput / processer.speed() + r.overhead() * t.throughput;
 
-        let mut sources = Vec::new();
-        for (n, c) in ingredients {
-            let s = self.solve_source(Flow {
-                name: n,
-                throughput: c * craft_throughput,
-            })?;
-            sources.push(s);
-        }
+        let inputs = r
+            .ingredients()
+            .map(|(n, c)| Throughput::new(n.clone(), c * craft_throughput))
+            .collect();
 
-        Ok(Some(Process {
-            throughput: Throughput::new(t.name, t.throughput),
+        Ok(Some(RecipeNode {
+            recipe: r.name(),
+            output: Throughput::new(t.name.clone(), t.throughput),
+            inputs,
             processer,
-            processer_num: unit_count,
+            processer_num: machine_count(machines),
+            machines,
             craft_per_sec: craft_throughput,
-            sources,
         }))
     }
 
-    fn solve_source(&mut self, t: Flow) -> Result<Source> {
-        if self.sources.get(&t.name).is_some() {
-            self.source_throughputs.add(t.clone());
-            return Ok(Source::Source(Throughput::new(t.name, t.throughput)));
+    /// Processer choice with the override of `recipe`, or else of `item`, applied.
+    ///
+    /// Pinned items always use their pinned processer.
+    fn processer_choice_for(&self, recipe: &str, item: &str) -> ProcesserChoice {
+        if let Some(pin) = self.pinned.get(item) {
+            return ProcesserChoice::new().processer(&pin.processer);
         }
 
-        if self.is_merged(&t.name) {
-            self.targets.add(t.clone());
-            return Ok(Source::Merged(Throughput::new(t.name, t.throughput)));
+        match self
+            .processer_overrides
+            .get(recipe)
+            .or_else(|| self.processer_overrides.get(item))
+        {
+            Some(o) => o.apply(&self.processer_choice),
+            None => self.processer_choice.clone(),
         }
+    }
 
-        if let Some(process) = self.solve_one(t.clone())? {
-            return Ok(Source::Process(process));
+    /// Derives the tree-shaped view of `graph`.
+    ///
+    /// Targets and merged items become the roots, other intermediates are nested under
+    /// their consumers with their share of the graph node.
+    fn derive_trees(
+        &self,
+        graph: &ProductionGraph,
+        merged: &HashSet<String>,
+    ) -> Vec<ProcessingTree> {
+        let mut roots: Vec<&ItemNode> = graph
+            .items
+            .iter()
+            .filter(|i| graph.producer(i.throughput.name()).is_some())
+            .filter(|i| i.is_target() || self.is_merged(merged, i.throughput.name()))
+            .collect();
+
+        roots.sort_by(|l, r| {
+            let (l, r) = (l.throughput.name(), r.throughput.name());
+            let ml = self.is_merged(merged, l);
+            let mr = self.is_merged(merged, r);
+            if ml && !mr {
+                return Ordering::Greater;
+            }
+            if !ml && mr {
+                return Ordering::Less;
+            }
+
+            self.recipe_set.compare(l, r, &self.sources)
+        });
+
+        roots
+            .into_iter()
+            .filter_map(|i| {
+                let name = i.throughput.name();
+                let throughput = if self.is_merged(merged, name) {
+                    graph.producer(name).unwrap().output.throughput()
+                } else {
+                    i.target
+                };
+
+                self.derive_process(graph, merged, name, throughput)
+            })
+            .map(|process| ProcessingTree { process })
+            .collect()
+    }
+
+    fn derive_process(
+        &self,
+        graph: &ProductionGraph,
+        merged: &HashSet<String>,
+        name: &str,
+        throughput: f64,
+    ) -> Option<Process> {
+        let node = graph.producer(name)?;
+        let share = throughput / node.output.throughput();
+
+        let sources = node
+            .inputs
+            .iter()
+            .flat_map(|i| self.derive_sources(graph, merged, i.name(), i.throughput() * share))
+            .collect();
+
+        Some(Process {
+            throughput: Throughput::new(name.to_string(), throughput),
+            recipe: node.recipe.clone(),
+            processer: node.processer.clone(),
+            processer_num: machine_count(node.machines * share),
+            craft_per_sec: node.craft_per_sec * share,
+            sources,
+        })
+    }
+
+    /// Splits the demand of a limited source into its imported share and the rest.
+    fn derive_sources(
+        &self,
+        graph: &ProductionGraph,
+        merged: &HashSet<String>,
+        name: &str,
+        throughput: f64,
+    ) -> Vec<Source> {
+        let item = match graph.items.iter().find(|i| i.throughput.name() == name) {
+            Some(i) if self.source_limits.contains_key(name) => i,
+            _ => return vec![self.derive_source(graph, merged, name, throughput)],
+        };
+
+        let imported = throughput * item.source / item.throughput.throughput();
+        let mut sources = Vec::new();
+        if imported > 0.0 {
+            sources.push(Source::Source(Throughput::new(name.to_string(), imported)));
+        }
+        // A shortfall is reported on its own instead of as a source.
+        if throughput - imported > SHORTFALL_TOLERANCE && graph.producer(name).is_some() {
+            sources.push(self.derive_source(graph, merged, name, throughput - imported));
+        }
+        sources
+    }
+
+    fn derive_source(
+        &self,
+        graph: &ProductionGraph,
+        merged: &HashSet<String>,
+        name: &str,
+        throughput: f64,
+    ) -> Source {
+        if self.sources.contains(name) {
+            return Source::Source(Throughput::new(name.to_string(), throughput));
         }
 
-        Ok(Source::Source(Throughput::new(t.name, t.throughput)))
+        if self.is_merged(merged, name) {
+            return Source::Merged(Throughput::new(name.to_string(), throughput));
+        }
+
+        if let Some(process) = self.derive_process(graph, merged, name, throughput) {
+            return Source::Process(process);
+        }
+
+        Source::Source(Throughput::new(name.to_string(), throughput))
+    }
+
+    /// Returns whether the demand of `name` is left to the oil block instead of recipes.
+    ///
+    /// Sources are balanced only when asked to, other oil products always are unless
+    /// a recipe makes them on its own.
+    fn is_balanced_oil(&self, name: &str) -> bool {
+        if ![HEAVY_OIL, LIGHT_OIL, PETROLEUM_GAS].contains(&name) {
+            return false;
+        }
+        if self.sources.contains(name) {
+            return self.balance_oil;
+        }
+        self.recipe_set.find_recipes(name).is_empty() && self.oil_recipe_set.recipes().count() > 0
     }
 
-    fn is_merged(&self, name: &str) -> bool {
-        if self.never_merged.get(name).is_some() {
+    fn is_merged(&self, merged: &HashSet<String>, name: &str) -> bool {
+        if self.never_merged.contains(name) {
             return false;
         }
-        self.all_merged || self.merged.get(name).is_some()
+        self.all_merged || merged.contains(name)
     }
 }
 
-#[derive(Debug)]
+fn machine_count(machines: f64) -> u64 {
+    (machines - MACHINE_TOLERANCE).ceil().max(0.0) as u64
+}
+
+#[derive(Debug, Default)]
+struct ItemAcc {
+    total: f64,
+    source: f64,
+    target: f64,
+    missing: bool,
+    shortfall: f64,
+}
+
+#[derive(Debug, Clone)]
 struct ItemThroughputs {
     map: BTreeMap<String, f64>,
 }
@@ -208,6 +764,10 @@ impl ItemThroughputs {
         self.map.insert(flow.name, throughput);
     }
 
+    fn multiply(&mut self, mult: f64) {
+        self.map.values_mut().for_each(|t| *t *= mult);
+    }
+
     fn iter(&self) -> Iter<String, f64> {
         self.map.iter()
     }
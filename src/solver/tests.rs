@@ -0,0 +1,407 @@
+use super::*;
+
+use crate::fuel::load_fuel_dict;
+use crate::recipe::load_recipes;
+
+fn solve(targets: &[(&str, f64)], sources: &[&str]) -> Solution {
+    let mut target_settings = TargetSettings::new();
+    for (n, t) in targets {
+        target_settings.add_target(n.to_string(), *t);
+    }
+    target_settings.add_sources(sources.iter().map(|s| s.to_string()).collect());
+
+    solve_settings(&target_settings)
+}
+
+fn solve_settings(target_settings: &TargetSettings) -> Solution {
+    try_solve(target_settings).unwrap()
+}
+
+fn try_solve(target_settings: &TargetSettings) -> Result<Solution> {
+    solver(target_settings).solve()
+}
+
+fn solver(target_settings: &TargetSettings) -> Solver {
+    let mut solver = Solver::new(
+        load_recipes("./data/recipes").unwrap(),
+        target_settings,
+        ProcSet::open_set().unwrap(),
+    );
+    solver.fuel_dict(load_fuel_dict("./data/fuel.yaml").unwrap());
+    solver.oil_recipe_set(load_recipes("./data/oil").unwrap());
+
+    solver
+}
+
+#[test]
+fn summary_should_aggregate_duplicated_processes() {
+    let solution = solve(
+        &[("automation-science-pack", 1.0), ("inserter", 1.0)],
+        &["iron-plate", "copper-plate"],
+    );
+
+    let gear_procs: Vec<&Process> = solution
+        .trees
+        .iter()
+        .flat_map(|t| &t.process.sources)
+        .filter_map(|s| match s {
+            Source::Process(p) if p.throughput.name() == "iron-gear-wheel" => Some(p),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(gear_procs.len(), 2);
+
+    let summary = solution.summary();
+    let gears: Vec<&ItemSummary> = summary
+        .iter()
+        .filter(|s| s.throughput.name() == "iron-gear-wheel")
+        .collect();
+    assert_eq!(gears.len(), 1);
+    assert_eq!(gears[0].recipe, gear_procs[0].recipe);
+
+    let expected: f64 = gear_procs.iter().map(|p| p.throughput.throughput()).sum();
+    assert!((gears[0].throughput.throughput() - expected).abs() < 1e-9);
+    assert!(gears[0].processer_num <= gear_procs.iter().map(|p| p.processer_num).sum());
+}
+
+#[test]
+fn graph_should_have_one_recipe_node_per_item() {
+    let solution = solve(
+        &[("automation-science-pack", 1.0), ("inserter", 1.0)],
+        &["iron-plate", "copper-plate"],
+    );
+    let graph = &solution.graph;
+
+    for (i, r) in graph.recipes.iter().enumerate() {
+        assert!(graph
+            .recipes
+            .iter()
+            .skip(i + 1)
+            .all(|o| o.output.name() != r.output.name()));
+    }
+
+    for item in &graph.items {
+        let produced = graph
+            .producer(item.throughput.name())
+            .map(|r| r.output.throughput())
+            .unwrap_or(0.0);
+        assert!((item.throughput.throughput() - produced - item.source).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn auto_merge_should_merge_shared_intermediates() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("automation-science-pack".to_string(), 1.0);
+    target_settings.add_target("inserter".to_string(), 1.0);
+    target_settings.add_sources(vec!["iron-plate".to_string(), "copper-plate".to_string()]);
+    target_settings.solver_settings_mut().auto_merge = Some(AutoMerge::default());
+
+    let solver = solver(&target_settings);
+    let solution = solver.solve().unwrap();
+
+    assert_eq!(
+        solution.auto_merged,
+        vec![AutoMerged {
+            name: "iron-gear-wheel".to_string(),
+            reason: MergeReason::Consumers(2),
+        }]
+    );
+    assert!(solution
+        .trees
+        .iter()
+        .any(|t| t.process.throughput.name() == "iron-gear-wheel"));
+
+    // solving again picks the same merges instead of treating them as settings
+    let again = solver.solve().unwrap();
+    assert_eq!(again.auto_merged, solution.auto_merged);
+    assert_eq!(again.trees.len(), solution.trees.len());
+}
+
+#[test]
+fn balance_oil_should_replace_oil_sources() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("plastic-bar".to_string(), 10.0);
+    target_settings.add_sources(vec!["coal".to_string(), "petroleum-gas".to_string()]);
+    target_settings.solver_settings_mut().balance_oil = true;
+
+    let solution = solve_settings(&target_settings);
+    let names: Vec<&str> = solution.sources.iter().map(Throughput::name).collect();
+
+    assert_eq!(names, vec!["coal", "crude-oil", "water"]);
+    assert!(solution.oil.is_some());
+}
+
+#[test]
+fn oil_products_should_share_one_balanced_block() {
+    let solution = solve(
+        &[("plastic-bar", 10.0), ("lubricant", 10.0), ("sulfur", 10.0)],
+        &["coal", "water", "crude-oil"],
+    );
+
+    for n in &[HEAVY_OIL, LIGHT_OIL, PETROLEUM_GAS] {
+        assert!(solution.graph.producer(n).is_none(), "{}", n);
+    }
+    let oil = solution.oil.unwrap();
+    assert_eq!(oil.refinery.recipe, "advanced-oil-processing");
+    assert!(oil.refinery.craft_per_sec > 0.0);
+    assert!(oil.surplus.is_empty());
+
+    let names: Vec<&str> = solution.sources.iter().map(Throughput::name).collect();
+    assert_eq!(names, vec!["coal", "crude-oil", "water"]);
+}
+
+#[test]
+fn mining_productivity_should_apply_to_drills() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("iron-ore".to_string(), 12.0);
+    target_settings.solver_settings_mut().mining_productivity = 20.0;
+
+    let solution = solve_settings(&target_settings);
+    let drills = solution.graph.producer("iron-ore").unwrap();
+
+    assert!((drills.craft_per_sec - 10.0).abs() < 1e-9);
+    assert_eq!(drills.processer_num, 20);
+}
+
+#[test]
+fn processer_overrides_should_apply_per_item() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("electronic-circuit".to_string(), 10.0);
+    target_settings.add_target("iron-gear-wheel".to_string(), 1.0);
+    target_settings.add_sources(vec!["iron-plate".to_string(), "copper-plate".to_string()]);
+
+    let overrides = &mut target_settings.solver_settings_mut().overrides;
+    overrides.insert(
+        "iron-gear-wheel".to_string(),
+        ProcesserOverride {
+            no_beacon: Some(true),
+            no_prod: Some(true),
+            no_speed: Some(true),
+            ..ProcesserOverride::default()
+        },
+    );
+    overrides.insert(
+        "electronic-circuit".to_string(),
+        ProcesserOverride {
+            processer: Some("assembler-p4-b8".to_string()),
+            ..ProcesserOverride::default()
+        },
+    );
+
+    let solution = solve_settings(&target_settings);
+    let processer = |name: &str| {
+        solution
+            .graph
+            .producer(name)
+            .unwrap()
+            .processer
+            .name()
+            .to_string()
+    };
+
+    assert_eq!(processer("iron-gear-wheel"), "assembler");
+    assert_eq!(processer("electronic-circuit"), "assembler-p4-b8");
+    assert_ne!(processer("copper-cable"), "assembler");
+}
+
+#[test]
+fn max_tier_should_limit_machines() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("iron-gear-wheel".to_string(), 1.0);
+    target_settings.add_target("iron-plate".to_string(), 1.0);
+    target_settings.add_sources(vec!["iron-ore".to_string()]);
+    target_settings.solver_settings_mut().max_tier = Some(1);
+
+    let solution = solve_settings(&target_settings);
+    let processer = |name: &str| {
+        solution
+            .graph
+            .producer(name)
+            .unwrap()
+            .processer
+            .name()
+            .to_string()
+    };
+
+    assert_eq!(processer("iron-gear-wheel"), "assembling-machine-1");
+    assert_eq!(processer("iron-plate"), "stone-furnace");
+}
+
+#[test]
+fn fuel_should_cover_burner_processers() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("iron-plate".to_string(), 5.0);
+    let settings = target_settings.solver_settings_mut();
+    settings.max_tier = Some(1);
+    settings.fuel = Some("coal".to_string());
+
+    let solution = solve_settings(&target_settings);
+    let fuel = solution.fuel.unwrap();
+
+    // 16 stone furnaces and 20 burner drills for the plates, drills for the coal itself
+    let burner_power: f64 = solution
+        .graph
+        .recipes
+        .iter()
+        .map(|r| r.machines * r.processer.burner_power())
+        .sum();
+    assert_eq!(fuel.name(), "coal");
+    assert!((fuel.throughput() - burner_power / 4000.0).abs() < 1e-6);
+    assert!(fuel.throughput() > (16.0 * 90.0 + 20.0 * 150.0) / 4000.0);
+    assert!(
+        (solution.graph.producer("coal").unwrap().output.throughput() - fuel.throughput()).abs()
+            < 1e-6
+    );
+}
+
+#[test]
+fn rocket_launch_should_occupy_silos() {
+    // one launch per minute
+    let solution = solve(
+        &[("space-science-pack", 1000.0 / 60.0)],
+        &[
+            "low-density-structure",
+            "rocket-fuel",
+            "rocket-control-unit",
+            "satellite",
+        ],
+    );
+
+    let part = solution.graph.producer("rocket-part").unwrap();
+    assert!((part.output.throughput() - 100.0 / 60.0).abs() < 1e-6);
+    assert!(part.processer.name().starts_with("rocket-silo"));
+
+    // productivity modules save parts, not the launch sequence of 40.33 s per 100 parts
+    let crafting = 3.0 * part.craft_per_sec / part.processer.speed();
+    assert!(part.craft_per_sec < part.output.throughput());
+    assert!((part.machines - (crafting + 40.333333 / 60.0)).abs() < 1e-6);
+    assert_eq!(part.processer_num, 2);
+
+    let launch = solution.graph.producer("space-science-pack").unwrap();
+    assert_eq!(launch.processer_num, 0);
+}
+
+#[test]
+fn supplies_should_scale_targets_to_the_binding_one() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("electronic-circuit".to_string(), 1.0);
+    target_settings.add_supply("iron-plate".to_string(), 10.0);
+    target_settings.add_supply("copper-plate".to_string(), 10.0);
+
+    let solution = solve_settings(&target_settings);
+    let supply = solution.fit.unwrap();
+
+    // a circuit takes 1 iron plate and 1.5 copper plates, less with productivity
+    assert_eq!(supply.binding, "copper-plate");
+    let circuit = solution.graph.producer("electronic-circuit").unwrap();
+    assert!((circuit.output.throughput() - supply.mult).abs() < 1e-6);
+
+    let used = |name: &str| {
+        supply
+            .supplies
+            .iter()
+            .find(|u| u.name == name)
+            .unwrap()
+            .used
+    };
+    assert!((used("copper-plate") - 10.0).abs() < 1e-6);
+    assert!(used("iron-plate") < 10.0);
+}
+
+#[test]
+fn supplies_should_count_after_oil_balancing() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("plastic-bar".to_string(), 1.0);
+    target_settings.add_sources(vec!["coal".to_string(), "petroleum-gas".to_string()]);
+    target_settings.add_supply("crude-oil".to_string(), 1000.0);
+    target_settings.solver_settings_mut().balance_oil = true;
+
+    let solution = solve_settings(&target_settings);
+    let fit = solution.fit.unwrap();
+    assert_eq!(fit.binding, "crude-oil");
+    assert!((fit.supplies[0].used - 1000.0).abs() < 1e-6);
+
+    let crude = solution.sources.iter().find(|s| s.name() == "crude-oil");
+    assert!((crude.unwrap().throughput() - 1000.0).abs() < 1e-6);
+}
+
+#[test]
+fn source_limits_should_produce_the_rest_or_fall_short() {
+    let solve_limited = |no_remainder: bool| {
+        let mut target_settings = TargetSettings::new();
+        target_settings.add_target("iron-gear-wheel".to_string(), 10.0);
+        target_settings.add_source("iron-plate".to_string());
+        target_settings.set_source_limit("iron-plate".to_string(), 5.0);
+        target_settings.solver_settings_mut().no_remainder = no_remainder;
+
+        solve_settings(&target_settings)
+    };
+
+    let solution = solve_limited(false);
+    let plates = solution.graph.producer("iron-gear-wheel").unwrap().inputs[0].throughput();
+    let smelted = solution.graph.producer("iron-plate").unwrap();
+    assert!((smelted.output.throughput() - (plates - 5.0)).abs() < 1e-6);
+    assert_eq!(solution.sources.len(), 1);
+    assert!((solution.sources[0].throughput() - 5.0).abs() < 1e-6);
+    assert!(solution.shortfalls.is_empty());
+
+    let solution = solve_limited(true);
+    assert!(solution.graph.producer("iron-plate").is_none());
+    assert_eq!(solution.shortfalls.len(), 1);
+    assert!((solution.shortfalls[0].throughput() - (plates - 5.0)).abs() < 1e-6);
+}
+
+#[test]
+fn source_limit_should_not_be_combined_with_a_supply() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("iron-gear-wheel".to_string(), 1.0);
+    target_settings.add_supply("iron-plate".to_string(), 10.0);
+    target_settings.set_source_limit("iron-plate".to_string(), 5.0);
+
+    let err = try_solve(&target_settings).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "iron-plate has both a supply and a source limit"
+    );
+}
+
+#[test]
+fn pinned_process_should_set_target_and_upstream_rates() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target("automation-science-pack".to_string(), 1.0);
+    target_settings.add_sources(vec!["iron-plate".to_string(), "copper-plate".to_string()]);
+    target_settings.solver_settings_mut().pinned.insert(
+        "iron-gear-wheel".to_string(),
+        Pin {
+            processer: "assembling-machine-2".to_string(),
+            count: 3,
+        },
+    );
+
+    let solution = solve_settings(&target_settings);
+    let fit = solution.fit.unwrap();
+    assert_eq!(fit.binding, "iron-gear-wheel");
+
+    // 3 machines of speed 0.75 make 4.5 gears/s, all of them go into red packs
+    let gear = solution.graph.producer("iron-gear-wheel").unwrap();
+    assert_eq!(gear.processer.name(), "assembling-machine-2");
+    assert_eq!(gear.processer_num, 3);
+    assert!((gear.output.throughput() - 4.5).abs() < 1e-6);
+    let pack = solution.graph.producer("automation-science-pack").unwrap();
+    assert!((pack.output.throughput() - fit.mult).abs() < 1e-6);
+    assert!(
+        (pack
+            .inputs
+            .iter()
+            .find(|i| i.name() == "iron-gear-wheel")
+            .unwrap()
+            .throughput()
+            - 4.5)
+            .abs()
+            < 1e-6
+    );
+
+    let plates: f64 = gear.inputs.iter().map(Throughput::throughput).sum();
+    assert!((plates - 9.0).abs() < 1e-6);
+}
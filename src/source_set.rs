@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::BufReader;
+
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::near_name::NameSet;
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceSet {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceSets(HashMap<String, SourceSet>);
+
+impl SourceSets {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Returns the sources of `name` and of every set it includes, without duplicates.
+    pub fn resolve(&self, name: &str) -> Result<Vec<String>> {
+        let mut sources = Vec::new();
+        self.resolve_into(name, &mut Vec::new(), &mut sources)?;
+        Ok(sources)
+    }
+
+    fn resolve_into(
+        &self,
+        name: &str,
+        path: &mut Vec<String>,
+        sources: &mut Vec<String>,
+    ) -> Result<()> {
+        if path.iter().any(|p| p == name) {
+            return Err(format_err!(
+                "source set includes itself: {} -> {}",
+                path.join(" -> "),
+                name
+            ));
+        }
+
+        let set = self.0.get(name).ok_or_else(|| {
+            let mut name_set = NameSet::new();
+            name_set.add_names(self.names());
+            format_err!(
+                "unknown source set: {}, Did you mean: {:?}?",
+                name,
+                name_set.find_nearest_names(name, 3)
+            )
+        })?;
+
+        path.push(name.to_string());
+        for i in &set.include {
+            self.resolve_into(i, path, sources)?;
+        }
+        path.pop();
+
+        for s in &set.sources {
+            if !sources.contains(s) {
+                sources.push(s.clone());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn load_source_sets(file_path: &str) -> Result<SourceSets> {
+    let file = fs::File::open(file_path)?;
+    let reader = BufReader::new(file);
+
+    let sets: SourceSets = serde_yaml::from_reader(reader)?;
+    Ok(sets)
+}
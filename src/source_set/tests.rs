@@ -0,0 +1,56 @@
+use super::*;
+
+fn sets(yaml: &str) -> SourceSets {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn resolve_should_follow_includes() {
+    let sets = sets(
+        r#"
+early:
+  sources: [iron-plate, copper-plate]
+oil:
+  sources: [crude-oil, water]
+bus:
+  include: [early, oil]
+  sources: [steel, water]
+"#,
+    );
+
+    assert_eq!(
+        sets.resolve("bus").unwrap(),
+        vec!["iron-plate", "copper-plate", "crude-oil", "water", "steel"]
+    );
+}
+
+#[test]
+fn resolve_should_reject_cycles_and_unknown_sets() {
+    let sets = sets(
+        r#"
+a:
+  include: [b]
+b:
+  include: [a]
+c:
+  include: [d]
+"#,
+    );
+
+    assert!(sets.resolve("a").is_err());
+    assert!(sets.resolve("c").is_err());
+}
+
+#[test]
+fn data_source_sets_should_resolve() {
+    let sets = load_source_sets("./data/source-sets.yaml").unwrap();
+
+    for n in sets.names() {
+        sets.resolve(n).unwrap();
+    }
+    assert!(sets.resolve("none").unwrap().is_empty());
+    assert!(sets
+        .resolve("basic")
+        .unwrap()
+        .contains(&"uranium-235".to_string()));
+}
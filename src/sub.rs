@@ -1,17 +1,30 @@
-use clap::{App, ArgMatches};
+use clap::{App, Arg, ArgMatches};
 use failure::Error;
 
+use crate::processer::ProcesserChoice;
+use crate::target::SolverSettings;
+
 pub mod check;
 pub mod mining;
 pub mod oil;
+pub mod patch;
+pub mod power;
+pub mod research;
+pub mod rocket;
 pub mod solve;
 pub mod stack;
+pub mod transport;
 
 use check::Check;
 use mining::Mining;
 use oil::Oil;
+use patch::Patch;
+use power::Power;
+use research::Research;
+use rocket::Rocket;
 use solve::Solve;
 use stack::Stack;
+use transport::TransportCmd;
 
 pub trait SubCmd {
     fn name(&self) -> &'static str;
@@ -24,7 +37,84 @@ pub fn sub_commands() -> Vec<Box<dyn SubCmd>> {
         Box::new(Check::new()),
         Box::new(Mining::new()),
         Box::new(Oil::new()),
+        Box::new(Patch::new()),
+        Box::new(Power::new()),
+        Box::new(Research::new()),
+        Box::new(Rocket::new()),
         Box::new(Stack::new()),
         Box::new(Solve::new()),
+        Box::new(TransportCmd::new()),
     ]
 }
+
+pub fn processer_choice_args(app: App<'static, 'static>) -> App<'static, 'static> {
+    let app = switch_args(app, "no-beacon", "beacon");
+    let app = switch_args(app, "no-speed", "speed");
+    let app = switch_args(app, "no-prod", "prod");
+    let app = switch_args(app, "allow-speed-only-beacon", "no-speed-only-beacon");
+
+    app.arg(
+        Arg::with_name("max-tier")
+            .long("max-tier")
+            .takes_value(true),
+    )
+}
+
+pub fn processer_choice(matches: &ArgMatches) -> Result<ProcesserChoice, Error> {
+    let mut settings = SolverSettings::default();
+    apply_processer_choice(matches, &mut settings)?;
+    Ok(settings.processer_choice())
+}
+
+/// Plain machines without modules or beacons, for tests counting machines.
+#[cfg(test)]
+pub fn plain_processer_choice() -> ProcesserChoice {
+    let settings = SolverSettings {
+        no_beacon: true,
+        no_speed: true,
+        no_prod: true,
+        ..SolverSettings::default()
+    };
+    settings.processer_choice()
+}
+
+/// Overrides the processer choice settings by the flags given, keeping the others.
+pub fn apply_processer_choice(
+    matches: &ArgMatches,
+    settings: &mut SolverSettings,
+) -> Result<(), Error> {
+    apply_switch(matches, &mut settings.no_beacon, "no-beacon", "beacon");
+    apply_switch(matches, &mut settings.no_speed, "no-speed", "speed");
+    apply_switch(matches, &mut settings.no_prod, "no-prod", "prod");
+    apply_switch(
+        matches,
+        &mut settings.allow_speed_only_beacon,
+        "allow-speed-only-beacon",
+        "no-speed-only-beacon",
+    );
+    if let Some(tier) = matches.value_of("max-tier") {
+        settings.max_tier = Some(tier.parse()?);
+    }
+
+    Ok(())
+}
+
+/// Adds a flag turning a setting on and one turning it off, for settings a target file
+/// may have turned on already.
+pub fn switch_args(
+    app: App<'static, 'static>,
+    on: &'static str,
+    off: &'static str,
+) -> App<'static, 'static> {
+    app.arg(Arg::with_name(on).long(on).conflicts_with(off))
+        .arg(Arg::with_name(off).long(off))
+}
+
+/// Sets `setting` by the flags of `switch_args`, keeping it when neither is given.
+pub fn apply_switch(matches: &ArgMatches, setting: &mut bool, on: &str, off: &str) {
+    if matches.is_present(on) {
+        *setting = true;
+    } else if matches.is_present(off) {
+        *setting = false;
+    }
+}
@@ -1,9 +1,13 @@
+use std::collections::BTreeSet;
+
 use clap::{App, Arg, ArgMatches, SubCommand};
 use failure::{format_err, Error};
 use semver::{Version, VersionReq};
 
-use crate::recipe::load_recipes;
+use crate::recipe::{load_recipes, Recipe, RecipeSet};
+use crate::source_set::load_source_sets;
 use crate::stack::load_stack_dict;
+use crate::technology::load_tech_tree;
 
 use super::SubCmd;
 
@@ -45,7 +49,12 @@ impl SubCmd for Check {
     }
 
     fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
-        let checks: Vec<(&str, CheckFunc)> = vec![("recipe", recipe_check), ("stack", stack_check)];
+        let checks: Vec<(&str, CheckFunc)> = vec![
+            ("recipe", recipe_check),
+            ("stack", stack_check),
+            ("source set", source_set_check),
+            ("technology", technology_check),
+        ];
 
         for (n, f) in checks {
             println!();
@@ -61,6 +70,14 @@ impl SubCmd for Check {
 
 type CheckFunc = fn(&ArgMatches) -> Result<(), Error>;
 
+/// Loads the recipes together with the oil recipes kept apart for oil balancing.
+fn load_all_recipes(data_dir: &str) -> Result<RecipeSet, Error> {
+    let mut recipes = load_recipes(&(data_dir.to_string() + "recipes"))?;
+    let oil_recipes = load_recipes(&(data_dir.to_string() + "oil"))?;
+    recipes.append_recipes(oil_recipes.recipes().cloned().collect());
+    Ok(recipes)
+}
+
 fn recipe_check(matches: &ArgMatches) -> Result<(), Error> {
     let allowed_version_str = matches.value_of("version").unwrap();
     let allowed_version = VersionReq::parse(allowed_version_str)?;
@@ -68,8 +85,7 @@ fn recipe_check(matches: &ArgMatches) -> Result<(), Error> {
     let error_limit = usize::from_str_radix(matches.value_of("error-limit").unwrap(), 10)?;
     let mut error_count = 0usize;
 
-    let recipe_dir = matches.value_of("data-dir").unwrap().to_string() + "recipes";
-    let recipes = load_recipes(&recipe_dir)?;
+    let recipes = load_all_recipes(matches.value_of("data-dir").unwrap())?;
     let all_results = recipes.all_results();
 
     for (n, r) in recipes.recipes().enumerate() {
@@ -136,8 +152,7 @@ fn recipe_check(matches: &ArgMatches) -> Result<(), Error> {
 
 fn stack_check(matches: &ArgMatches) -> Result<(), Error> {
     let data_dir = matches.value_of("data-dir").unwrap();
-    let recipe_dir = data_dir.to_string() + "recipes";
-    let recipes = load_recipes(&recipe_dir)?;
+    let recipes = load_all_recipes(data_dir)?;
     let all_results = recipes.all_results();
 
     let stack_dict = load_stack_dict(&(data_dir.to_string() + "stack-size.yaml"))?;
@@ -164,3 +179,81 @@ fn stack_check(matches: &ArgMatches) -> Result<(), Error> {
 
     Ok(())
 }
+
+fn source_set_check(matches: &ArgMatches) -> Result<(), Error> {
+    let data_dir = matches.value_of("data-dir").unwrap();
+    let recipes = load_all_recipes(data_dir)?;
+    let all_results = recipes.all_results();
+
+    let source_sets = load_source_sets(&(data_dir.to_string() + "source-sets.yaml"))?;
+    let mut error_count = 0usize;
+
+    let mut names: Vec<&str> = source_sets.names().collect();
+    names.sort();
+
+    for n in names {
+        match source_sets.resolve(n) {
+            Ok(sources) => {
+                for s in sources.iter().filter(|s| !all_results.contains(*s)) {
+                    println!("source set {}: \"{}\" is not produced by any recipe.", n, s);
+                }
+            }
+            Err(err) => {
+                error_count += 1;
+                println!("source set {}: {}", n, err);
+            }
+        }
+    }
+
+    if error_count > 0 {
+        Err(format_err!("Found {} errors.", error_count))
+    } else {
+        Ok(())
+    }
+}
+
+fn technology_check(matches: &ArgMatches) -> Result<(), Error> {
+    let data_dir = matches.value_of("data-dir").unwrap();
+    let recipes = load_all_recipes(data_dir)?;
+    let recipe_names: BTreeSet<String> = recipes.recipes().map(Recipe::name).collect();
+
+    let tech_tree = load_tech_tree(&(data_dir.to_string() + "technologies.yaml"))?;
+    let unlocked = tech_tree.all_recipes();
+    let mut error_count = 0usize;
+
+    let mut names: Vec<&str> = tech_tree.names().collect();
+    names.sort();
+
+    for n in names {
+        if let Err(err) = tech_tree.unlocks(&[n.to_string()]) {
+            error_count += 1;
+            println!("technology {}: {}", n, err);
+        }
+    }
+
+    for r in recipes.recipes() {
+        if !unlocked.contains(r.name().as_str()) {
+            error_count += 1;
+            println!(
+                "{}: \"{}\" is not unlocked by any technology.",
+                r.file_path("unknown"),
+                r.name()
+            );
+        }
+    }
+
+    let mut unknowns: Vec<&str> = unlocked
+        .into_iter()
+        .filter(|r| !recipe_names.contains(*r))
+        .collect();
+    unknowns.sort();
+    for r in unknowns {
+        println!("technology recipe \"{}\" is not in the recipes.", r);
+    }
+
+    if error_count > 0 {
+        Err(format_err!("Found {} errors.", error_count))
+    } else {
+        Ok(())
+    }
+}
@@ -1,8 +1,15 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
-use failure::Error;
+use failure::{format_err, Error};
 
-use super::SubCmd;
-use crate::consts::BELT_THROUGHPUT;
+use super::{processer_choice, processer_choice_args, SubCmd};
+use crate::consts::LANE_THROUGHPUT;
+use crate::processer::{ProcSet, ProcesserChoice};
+use crate::recipe::{load_recipes, Recipe, RecipeSet};
+
+#[cfg(test)]
+mod tests;
+
+const ORES: [&str; 5] = ["iron-ore", "copper-ore", "stone", "coal", "uranium-ore"];
 
 pub struct Mining();
 
@@ -18,7 +25,7 @@ impl SubCmd for Mining {
     }
 
     fn command_args(&self) -> App<'static, 'static> {
-        SubCommand::with_name(self.name())
+        let app = SubCommand::with_name(self.name())
             .about("Calculate mining furnace lines.")
             .arg(
                 Arg::with_name("bonus")
@@ -34,25 +41,169 @@ impl SubCmd for Mining {
                     .short("c")
                     .default_value("1"),
             )
+            .arg(Arg::with_name("ore").multiple(true));
+
+        processer_choice_args(app)
     }
 
     fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
         let bonus: u64 = matches.value_of("bonus").unwrap().parse()?;
         let count: u64 = matches.value_of("count").unwrap().parse()?;
+        let ores: Vec<&str> = match matches.values_of("ore") {
+            Some(ores) => ores.collect(),
+            None => ORES.to_vec(),
+        };
+
+        let recipe_set = load_recipes("./data/recipes")?;
+        let processer_set = ProcSet::open_set()?;
+        let processer_choice = processer_choice(matches)?;
 
         println!("Bonus: {}%, Count: {}", bonus, count);
         println!();
 
-        let prod = (bonus as f64) / 100.0 + 1.0;
-        let ore_output = 0.5 * prod * (count as f64);
-        let plate_output = 1.2 * ore_output;
+        println!(
+            "| {:^12} | {:^20} | {:^8} | {:^6} | {:^24} | {:^12} | {:^20} | {:^8} | {:^6} |",
+            "ore", "drill", "ore/s", "lanes", "fluid", "product", "furnace", "output/s", "lanes"
+        );
+        println!("|:-------------|:---------------------|---------:|-------:|:-------------------------|:-------------|:---------------------|---------:|-------:|");
+
+        for ore in ores {
+            let line = mining_line(
+                &recipe_set,
+                &processer_set,
+                &processer_choice,
+                ore,
+                (bonus as f64) / 100.0,
+                count,
+            )?;
+
+            let fluid = line
+                .fluids
+                .iter()
+                .map(|(n, a)| format!("{} {:.1}/s", n, a))
+                .collect::<Vec<_>>()
+                .join(", ");
 
-        let out_belt = plate_output / BELT_THROUGHPUT;
+            print!(
+                "| {:<12} | {:<20} | {:>8.2} | {:>6.2} | {:<24} ",
+                ore,
+                format!("{} x {}", line.drill, count),
+                line.ore_per_sec,
+                line.ore_per_sec / LANE_THROUGHPUT,
+                fluid,
+            );
 
-        println!("Ore Output:   {:.1}", ore_output);
-        println!("Plate Output: {:.1}", plate_output);
-        println!("Lanes:  {:.1}", out_belt);
+            match line.smelting {
+                Some(s) => println!(
+                    "| {:<12} | {:<20} | {:>8.2} | {:>6.2} |",
+                    s.product,
+                    format!("{} x {}", s.furnace, s.furnace_num),
+                    s.output_per_sec,
+                    s.output_per_sec / LANE_THROUGHPUT,
+                ),
+                None => println!("| {:<12} | {:<20} | {:>8} | {:>6} |", "-", "-", "-", "-"),
+            }
+        }
 
         Ok(())
     }
 }
+
+struct MiningLine {
+    drill: String,
+    ore_per_sec: f64,
+    fluids: Vec<(String, f64)>,
+    smelting: Option<Smelting>,
+}
+
+struct Smelting {
+    product: String,
+    furnace: String,
+    furnace_num: u64,
+    output_per_sec: f64,
+}
+
+fn mining_line(
+    recipe_set: &RecipeSet,
+    processer_set: &ProcSet,
+    processer_choice: &ProcesserChoice,
+    ore: &str,
+    bonus: f64,
+    count: u64,
+) -> Result<MiningLine, Error> {
+    let recipe = recipe_set
+        .find_recipes(ore)
+        .into_iter()
+        .find(|r| r.recipe_type() == "mining-drill")
+        .ok_or_else(|| format_err!("no mining recipe for {}", ore))?;
+
+    let drill = processer_set
+        .best_processer(
+            recipe.recipe_type(),
+            recipe.ingredients_count(),
+            recipe.is_material(),
+            0.0,
+            processer_choice,
+        )?
+        .with_productivity_bonus(bonus);
+
+    let craft_per_sec = (count as f64) * drill.speed() / recipe.cost();
+    let ore_per_sec = craft_per_sec * drill.productivity() * recipe.result_num(ore);
+    let fluids = recipe
+        .ingredients()
+        .map(|(n, c)| (n.to_string(), c * craft_per_sec))
+        .collect();
+
+    let smelting = match find_smelting(recipe_set, ore) {
+        Some(r) => Some(smelting(
+            processer_set,
+            processer_choice,
+            r,
+            ore,
+            ore_per_sec,
+        )?),
+        None => None,
+    };
+
+    Ok(MiningLine {
+        drill: drill.name().to_string(),
+        ore_per_sec,
+        fluids,
+        smelting,
+    })
+}
+
+fn find_smelting<'a>(recipe_set: &'a RecipeSet, ore: &str) -> Option<&'a Recipe> {
+    recipe_set
+        .recipes()
+        .find(|r| r.recipe_type() == "furnace" && r.ingredient_num(ore) > 0.0)
+}
+
+fn smelting(
+    processer_set: &ProcSet,
+    processer_choice: &ProcesserChoice,
+    recipe: &Recipe,
+    ore: &str,
+    ore_per_sec: f64,
+) -> Result<Smelting, Error> {
+    let craft_per_sec = ore_per_sec / recipe.ingredient_num(ore);
+    let furnace = processer_set.best_processer(
+        recipe.recipe_type(),
+        recipe.ingredients_count(),
+        recipe.is_material(),
+        recipe.cost() * craft_per_sec,
+        processer_choice,
+    )?;
+
+    let (product, result_num) = recipe
+        .results()
+        .next()
+        .ok_or_else(|| format_err!("no result for {}", recipe.name()))?;
+
+    Ok(Smelting {
+        product: product.to_string(),
+        furnace: furnace.name().to_string(),
+        furnace_num: (recipe.cost() * craft_per_sec / furnace.speed()).ceil() as u64,
+        output_per_sec: craft_per_sec * furnace.productivity() * result_num,
+    })
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+use crate::sub::plain_processer_choice;
+
+fn line(ore: &str, bonus: f64, count: u64) -> MiningLine {
+    mining_line(
+        &load_recipes("./data/recipes").unwrap(),
+        &ProcSet::open_set().unwrap(),
+        &plain_processer_choice(),
+        ore,
+        bonus,
+        count,
+    )
+    .unwrap()
+}
+
+#[test]
+fn mining_line_should_apply_bonus_to_drills() {
+    // 10 drills of speed 0.5 on 1 s cycles, 20% more by the bonus
+    let line = line("iron-ore", 0.2, 10);
+
+    assert_eq!(line.drill, "mining-drill");
+    assert!((line.ore_per_sec - 6.0).abs() < 1e-9);
+    assert!(line.fluids.is_empty());
+
+    let smelting = line.smelting.unwrap();
+    assert_eq!(smelting.product, "iron-plate");
+    assert_eq!(smelting.furnace, "furnace");
+    assert!((smelting.output_per_sec - 6.0).abs() < 1e-9);
+    // 3.2 s per plate at speed 2
+    assert_eq!(smelting.furnace_num, 10);
+}
+
+#[test]
+fn mining_line_should_count_fluids_of_drills() {
+    // 2 drills of speed 0.5 on 2 s cycles
+    let line = line("uranium-ore", 0.0, 2);
+
+    assert!((line.ore_per_sec - 0.5).abs() < 1e-9);
+    assert_eq!(line.fluids, vec![("sulfuric-acid".to_string(), 0.5)]);
+    assert!(line.smelting.is_none());
+}
@@ -1,13 +1,13 @@
+use std::collections::BTreeMap;
+
 use clap::{App, Arg, ArgMatches, SubCommand};
 use failure::Error;
 
-use super::SubCmd;
+use crate::oil::{balance, OilDemand, HEAVY_OIL, LIGHT_OIL, PETROLEUM_GAS};
+use crate::processer::ProcSet;
+use crate::recipe::load_recipes;
 
-const REFINERY_COUNT_PER_LINE: usize = 8;
-const REFINERY_SPEED: f64 = 5.55;
-const REFINERY_PROD: f64 = 1.3;
-const HEAVY_OIL_CRACKING_PROD: f64 = 1.1;
-const LIGHT_OIL_CRACKING_PROD: f64 = 1.3;
+use super::{processer_choice, processer_choice_args, SubCmd};
 
 pub struct Oil();
 
@@ -23,66 +23,89 @@ impl SubCmd for Oil {
     }
 
     fn command_args(&self) -> App<'static, 'static> {
-        SubCommand::with_name(self.name()).arg(
-            Arg::with_name("count")
-                .long("count")
-                .short("c")
-                .default_value("1")
-                .takes_value(true),
-        )
+        let app = SubCommand::with_name(self.name())
+            .about("Calculate refineries and cracking plants for oil products.")
+            .arg(
+                Arg::with_name(HEAVY_OIL)
+                    .long(HEAVY_OIL)
+                    .default_value("0")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name(LIGHT_OIL)
+                    .long(LIGHT_OIL)
+                    .default_value("0")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name(PETROLEUM_GAS)
+                    .long(PETROLEUM_GAS)
+                    .default_value("0")
+                    .takes_value(true),
+            );
+
+        processer_choice_args(app)
     }
 
     fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
-        let count = matches.value_of("count").unwrap().parse::<usize>()?;
+        let demand = OilDemand {
+            heavy_oil: matches.value_of(HEAVY_OIL).unwrap().parse()?,
+            light_oil: matches.value_of(LIGHT_OIL).unwrap().parse()?,
+            petroleum_gas: matches.value_of(PETROLEUM_GAS).unwrap().parse()?,
+        };
+
+        let balance = balance(
+            &load_recipes("./data/oil")?,
+            &ProcSet::open_set()?,
+            &processer_choice(matches)?,
+            demand,
+        )?;
 
         println!(
-            "| {:^24} | {:^10} | {:^10} | {:^10} | {:^10} | {:^15} |",
-            "process", "crude oil", "water", "heavy oil", "light oil", "petroleum gas"
+            "| {:^24} | {:^24} | {:^10} | {:^10} | {:^10} | {:^10} | {:^15} |",
+            "process", "processer", "crude oil", "water", "heavy oil", "light oil", "petroleum gas"
         );
-        println!("|:------------------------:|-----------:|-----------:|-----------:|-----------:|----------------:|");
-
-        // refinary
-        let refinery_craft_per_sec =
-            (REFINERY_COUNT_PER_LINE as f64) * (count as f64) * REFINERY_SPEED / 5.0;
-        let refinary = LiquidFlow {
-            name: "refinary",
-            crude_oil: -100.0 * refinery_craft_per_sec,
-            water: -50.0 * refinery_craft_per_sec,
-            heavy_oil: 10.0 * REFINERY_PROD * refinery_craft_per_sec,
-            light_oil: 45.0 * REFINERY_PROD * refinery_craft_per_sec,
-            petroleum_gas: 55.0 * REFINERY_PROD * refinery_craft_per_sec,
-        };
-        println!("{}", refinary);
-
-        let heavy_oil_cracking_per_sec = refinary.heavy_oil / 40.0;
-        let heavy_oil_cracking_max = LiquidFlow {
-            name: "heavy oil cracking (max)",
-            heavy_oil: 0.0,
-            light_oil: refinary.light_oil
-                + 30.0 * HEAVY_OIL_CRACKING_PROD * heavy_oil_cracking_per_sec,
-            water: refinary.water + 30.0 * heavy_oil_cracking_per_sec,
-            ..refinary
-        };
-        println!("{}", heavy_oil_cracking_max);
-
-        let light_oil_cracking_per_sec = heavy_oil_cracking_max.light_oil / 30.0;
-        let light_oil_cracking_max = LiquidFlow {
-            name: "light oil cracking (max)",
-            light_oil: 0.0,
-            petroleum_gas: heavy_oil_cracking_max.petroleum_gas
-                + 20.0 * LIGHT_OIL_CRACKING_PROD * light_oil_cracking_per_sec,
-            water: heavy_oil_cracking_max.water + 30.0 * light_oil_cracking_per_sec,
-            ..heavy_oil_cracking_max
+        println!("|:------------------------:|:------------------------:|-----------:|-----------:|-----------:|-----------:|----------------:|");
+
+        let mut total = LiquidFlow::default();
+        for p in balance.processes() {
+            let flow = LiquidFlow::from_flows(&p.flows);
+            total = total.add(&flow);
+            print_row(
+                &p.recipe,
+                &format!("{} x {}", p.processer.name(), p.processer_num),
+                &flow,
+            );
+        }
+
+        let demand_flow = LiquidFlow {
+            heavy_oil: 0.0 - demand.heavy_oil,
+            light_oil: 0.0 - demand.light_oil,
+            petroleum_gas: 0.0 - demand.petroleum_gas,
+            ..LiquidFlow::default()
         };
-        println!("{}", light_oil_cracking_max);
+        print_row("demand", "", &demand_flow);
+        print_row("total", "", &total.add(&demand_flow));
 
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-struct LiquidFlow<'a> {
-    name: &'a str,
+fn print_row(name: &str, processer: &str, flow: &LiquidFlow) {
+    println!(
+        "| {:<24} | {:<24} | {:>10.1} | {:>10.1} | {:>10.1} | {:>10.1} | {:>15.1} |",
+        name,
+        processer,
+        flow.crude_oil,
+        flow.water,
+        flow.heavy_oil,
+        flow.light_oil,
+        flow.petroleum_gas
+    );
+}
+
+#[derive(Debug, Clone, Default)]
+struct LiquidFlow {
     crude_oil: f64,
     water: f64,
     heavy_oil: f64,
@@ -90,17 +113,26 @@ struct LiquidFlow<'a> {
     petroleum_gas: f64,
 }
 
-impl<'a> std::fmt::Display for LiquidFlow<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "| {:<24} | {:>10.1} | {:>10.1} | {:>10.1} | {:>10.1} | {:>15.1} |",
-            self.name,
-            self.crude_oil,
-            self.water,
-            self.heavy_oil,
-            self.light_oil,
-            self.petroleum_gas
-        )
+impl LiquidFlow {
+    fn from_flows(flows: &BTreeMap<String, f64>) -> LiquidFlow {
+        let get = |n: &str| flows.get(n).cloned().unwrap_or(0.0);
+
+        LiquidFlow {
+            crude_oil: get("crude-oil"),
+            water: get("water"),
+            heavy_oil: get(HEAVY_OIL),
+            light_oil: get(LIGHT_OIL),
+            petroleum_gas: get(PETROLEUM_GAS),
+        }
+    }
+
+    fn add(&self, other: &LiquidFlow) -> LiquidFlow {
+        LiquidFlow {
+            crude_oil: self.crude_oil + other.crude_oil,
+            water: self.water + other.water,
+            heavy_oil: self.heavy_oil + other.heavy_oil,
+            light_oil: self.light_oil + other.light_oil,
+            petroleum_gas: self.petroleum_gas + other.petroleum_gas,
+        }
     }
 }
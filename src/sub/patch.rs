@@ -0,0 +1,210 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure::{format_err, Error};
+
+use super::solve::{solve_args, solver, target_settings};
+use super::SubCmd;
+use crate::processer::{ProcSet, ProcesserChoice};
+use crate::recipe::{load_recipes, Recipe, RecipeSet};
+use crate::solution::Solution;
+
+#[cfg(test)]
+mod tests;
+
+/// Tiles covered by the 5x5 mining area of an electric mining drill, the default of
+/// `--drill-area`. Burner mining drills cover 2x2 tiles.
+const ELECTRIC_DRILL_AREA: &str = "25";
+
+pub struct Patch();
+
+impl Patch {
+    pub fn new() -> Patch {
+        Patch()
+    }
+}
+
+impl SubCmd for Patch {
+    fn name(&self) -> &'static str {
+        "patch"
+    }
+
+    fn command_args(&self) -> App<'static, 'static> {
+        solve_args(SubCommand::with_name(self.name()))
+            .about("Estimate how long ore patches last for a solved target.")
+            .arg(
+                Arg::with_name("amount")
+                    .long("amount")
+                    .short("a")
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("ore")
+                    .long("ore")
+                    .short("o")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true),
+            )
+            .arg(Arg::with_name("size").long("size").takes_value(true))
+            .arg(
+                Arg::with_name("drill-area")
+                    .long("drill-area")
+                    .takes_value(true)
+                    .default_value(ELECTRIC_DRILL_AREA),
+            )
+    }
+
+    fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
+        let amount: f64 = matches.value_of("amount").unwrap().parse()?;
+        let size: Option<f64> = match matches.value_of("size") {
+            Some(s) => Some(s.parse()?),
+            None => None,
+        };
+        let drill_area: f64 = matches.value_of("drill-area").unwrap().parse()?;
+
+        let target_settings = target_settings(matches)?;
+        let solution = solver(&target_settings)?.solve()?;
+        let bonus = target_settings.solver_settings().mining_productivity / 100.0;
+
+        let recipe_set = load_recipes("./data/recipes")?;
+        let processer_set = ProcSet::open_set()?;
+        let processer_choice = target_settings.solver_settings().processer_choice();
+
+        let mut demands = ore_demands(&recipe_set, &solution);
+        if let Some(ores) = matches.values_of("ore") {
+            let ores: Vec<&str> = ores.collect();
+            demands.retain(|(n, _)| ores.contains(&n.as_str()));
+        }
+        if demands.is_empty() {
+            return Err(format_err!("no mined ore in the solution"));
+        }
+
+        println!(
+            "Amount: {}, Mining productivity: {}%",
+            amount,
+            bonus * 100.0
+        );
+        if let Some(size) = size {
+            println!(
+                "Size: {} tiles, {:.0} drills cover it",
+                size,
+                size / drill_area
+            );
+        }
+        println!();
+
+        println!(
+            "| {:^12} | {:^8} | {:^20} | {:^10} | {:^10} | {:^40} |",
+            "ore", "ore/s", "drill", "lifetime", "with prod", "fluid"
+        );
+        println!("|:-------------|---------:|:---------------------|-----------:|-----------:|:-----------------------------------------|");
+
+        for (ore, ore_per_sec) in demands {
+            let life = patch_life(
+                &recipe_set,
+                &processer_set,
+                &processer_choice,
+                &ore,
+                ore_per_sec,
+                amount,
+                bonus,
+            )?;
+
+            let fluid = life
+                .fluids
+                .iter()
+                .map(|(n, per_sec, total)| format!("{} {:.1}/s, {:.0} total", n, per_sec, total))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            println!(
+                "| {:<12} | {:>8.2} | {:<20} | {:>10} | {:>10} | {:<40} |",
+                ore,
+                ore_per_sec,
+                format!("{} x {}", life.drill, life.drill_num),
+                format_duration(life.seconds),
+                format_duration(life.seconds_with_bonus),
+                fluid,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+struct PatchLife {
+    drill: String,
+    drill_num: u64,
+    seconds: f64,
+    seconds_with_bonus: f64,
+    /// Fluid name, consumption per second and total over the patch.
+    fluids: Vec<(String, f64, f64)>,
+}
+
+/// Mined ores of `solution` and their throughput, whether solved as sources or by drills.
+fn ore_demands(recipe_set: &RecipeSet, solution: &Solution) -> Vec<(String, f64)> {
+    solution
+        .graph
+        .items
+        .iter()
+        .filter(|i| find_drilling(recipe_set, i.throughput.name()).is_some())
+        .map(|i| (i.throughput.name().to_string(), i.throughput.throughput()))
+        .collect()
+}
+
+fn find_drilling<'a>(recipe_set: &'a RecipeSet, ore: &str) -> Option<&'a Recipe> {
+    recipe_set
+        .find_recipes(ore)
+        .into_iter()
+        .find(|r| r.recipe_type() == "mining-drill")
+}
+
+fn patch_life(
+    recipe_set: &RecipeSet,
+    processer_set: &ProcSet,
+    processer_choice: &ProcesserChoice,
+    ore: &str,
+    ore_per_sec: f64,
+    amount: f64,
+    bonus: f64,
+) -> Result<PatchLife, Error> {
+    let recipe = find_drilling(recipe_set, ore)
+        .ok_or_else(|| format_err!("no mining recipe for {}", ore))?;
+    let result_num = recipe.result_num(ore);
+
+    let plain = processer_set.best_processer(
+        recipe.recipe_type(),
+        recipe.ingredients_count(),
+        recipe.is_material(),
+        recipe.cost() * ore_per_sec / result_num,
+        processer_choice,
+    )?;
+    let drill = plain.with_productivity_bonus(bonus);
+
+    // The patch is depleted once per mining cycle, productivity only adds output.
+    let seconds = amount * plain.productivity() / ore_per_sec;
+    let craft_per_sec = ore_per_sec / result_num / drill.productivity();
+    let seconds_with_bonus = amount / result_num / craft_per_sec;
+
+    let fluids = recipe
+        .ingredients()
+        .map(|(n, c)| (n.to_string(), c * craft_per_sec, c * amount / result_num))
+        .collect();
+
+    Ok(PatchLife {
+        drill: drill.name().to_string(),
+        drill_num: (recipe.cost() * craft_per_sec / drill.speed()).ceil() as u64,
+        seconds,
+        seconds_with_bonus,
+        fluids,
+    })
+}
+
+fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return "-".to_string();
+    }
+
+    let minutes = (seconds / 60.0).floor() as u64;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
@@ -0,0 +1,46 @@
+use super::*;
+
+use crate::sub::plain_processer_choice;
+
+fn life(ore: &str, ore_per_sec: f64, amount: f64, bonus: f64) -> PatchLife {
+    patch_life(
+        &load_recipes("./data/recipes").unwrap(),
+        &ProcSet::open_set().unwrap(),
+        &plain_processer_choice(),
+        ore,
+        ore_per_sec,
+        amount,
+        bonus,
+    )
+    .unwrap()
+}
+
+#[test]
+fn patch_life_should_stretch_with_mining_productivity() {
+    let life = life("iron-ore", 10.0, 36000.0, 0.5);
+
+    assert_eq!(life.drill, "mining-drill");
+    assert!((life.seconds - 3600.0).abs() < 1e-9);
+    assert!((life.seconds_with_bonus - 5400.0).abs() < 1e-9);
+    // 10 ore/s of 1 s cycles at speed 0.5 and 150% productivity
+    assert_eq!(life.drill_num, 14);
+    assert!(life.fluids.is_empty());
+}
+
+#[test]
+fn patch_life_should_count_mining_fluids() {
+    let life = life("uranium-ore", 1.0, 1000.0, 0.0);
+
+    assert!((life.seconds - 1000.0).abs() < 1e-9);
+    assert_eq!(life.drill_num, 4);
+    assert_eq!(
+        life.fluids,
+        vec![("sulfuric-acid".to_string(), 1.0, 1000.0)]
+    );
+}
+
+#[test]
+fn format_duration_should_show_hours_and_minutes() {
+    assert_eq!(format_duration(5400.0), "1h 30m");
+    assert_eq!(format_duration(f64::INFINITY), "-");
+}
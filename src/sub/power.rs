@@ -0,0 +1,157 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure::{format_err, Error};
+
+use crate::formatter::formatter_by_name;
+use crate::recipe::{load_recipes, Recipe, RecipeSet};
+use crate::target::TargetSettings;
+
+use super::solve::{apply_target_args, recipe_solver, solver_args};
+use super::SubCmd;
+
+/// Pseudo item of electric power, one per second is one MW.
+const ELECTRICITY: &str = "electricity";
+const REACTOR_TYPE: &str = "nuclear-reactor";
+/// Burnt by boilers unless --fuel says otherwise.
+const DEFAULT_FUEL: &str = "coal";
+
+/// Power plants and the recipe type generating their electricity.
+const PLANTS: [(&str, &str); 3] = [
+    ("steam", "steam-engine"),
+    ("solar", "solar-panel"),
+    ("nuclear", "steam-turbine"),
+];
+
+pub struct Power();
+
+impl Power {
+    pub fn new() -> Power {
+        Power()
+    }
+}
+
+impl SubCmd for Power {
+    fn name(&self) -> &'static str {
+        "power"
+    }
+
+    fn command_args(&self) -> App<'static, 'static> {
+        solver_args(SubCommand::with_name(self.name()))
+            .about("Calculate power plants for a power demand in MW.")
+            .arg(
+                Arg::with_name("plant")
+                    .long("plant")
+                    .short("p")
+                    .possible_values(&["steam", "solar", "nuclear"])
+                    .default_value("steam"),
+            )
+            .arg(
+                Arg::with_name("reactors")
+                    .long("reactors")
+                    .takes_value(true)
+                    .default_value("2x2"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .short("f")
+                    .default_value("text"),
+            )
+            .arg(Arg::with_name("mw").required(true))
+    }
+
+    fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
+        let mw: f64 = matches.value_of("mw").unwrap().parse()?;
+        let recipe_set = plant_recipes(
+            matches.value_of("plant").unwrap(),
+            matches.value_of("reactors").unwrap(),
+        )?;
+
+        let mut target_settings = TargetSettings::new();
+        target_settings.add_target(ELECTRICITY.to_string(), mw);
+        target_settings.solver_settings_mut().fuel = Some(DEFAULT_FUEL.to_string());
+        apply_target_args(matches, &mut target_settings, "basic")?;
+
+        let solution = recipe_solver(&target_settings, recipe_set)?.solve()?;
+
+        let mut formatter = formatter_by_name(matches.value_of("format").unwrap())?;
+        formatter.format(&solution)?;
+
+        Ok(())
+    }
+}
+
+/// Recipes with electricity generated only by `plant`, reactors laid out as `reactors`.
+fn plant_recipes(plant: &str, reactors: &str) -> Result<RecipeSet, Error> {
+    let generator = PLANTS
+        .iter()
+        .find(|p| p.0 == plant)
+        .map(|p| p.1)
+        .ok_or_else(|| format_err!("unknown plant: {}", plant))?;
+
+    let mut recipe_set = load_recipes("./data/recipes")?;
+    recipe_set.retain(|r| !r.has_result(ELECTRICITY) || r.recipe_type() == generator);
+    if plant == "nuclear" {
+        apply_neighbour_bonus(&mut recipe_set, neighbour_bonus(reactors)?);
+    }
+
+    Ok(recipe_set)
+}
+
+/// Average neighbour bonus of reactors laid out in a `rows`x`columns` grid.
+///
+/// Each reactor gets +100% for every working neighbour.
+fn neighbour_bonus(layout: &str) -> Result<f64, Error> {
+    let invalid = || format_err!("reactors must be like 2x2: {}", layout);
+
+    let mut parts = layout.splitn(2, 'x');
+    let rows: u64 = parts.next().ok_or_else(invalid)?.parse()?;
+    let columns: u64 = parts.next().ok_or_else(invalid)?.parse()?;
+    if rows == 0 || columns == 0 {
+        return Err(invalid());
+    }
+
+    let pairs = rows * (columns - 1) + columns * (rows - 1);
+    Ok(2.0 * pairs as f64 / (rows * columns) as f64)
+}
+
+fn apply_neighbour_bonus(recipe_set: &mut RecipeSet, bonus: f64) {
+    let reactors: Vec<Recipe> = recipe_set
+        .recipes()
+        .filter(|r| r.recipe_type() == REACTOR_TYPE)
+        .map(|r| r.with_result_mult(1.0 + bonus))
+        .collect();
+
+    recipe_set.retain(|r| r.recipe_type() != REACTOR_TYPE);
+    recipe_set.append_recipes(reactors);
+}
+
+#[test]
+fn neighbour_bonus_by_layout() {
+    let params = [("1x1", 0.0), ("1x2", 1.0), ("2x2", 2.0), ("2x4", 2.5)];
+
+    for p in &params {
+        assert!(
+            (neighbour_bonus(p.0).unwrap() - p.1).abs() < 1e-9,
+            "{}",
+            p.0
+        );
+    }
+
+    assert!(neighbour_bonus("2").is_err());
+}
+
+#[test]
+fn nuclear_reactors_for_demand() {
+    let mut target_settings = TargetSettings::new();
+    target_settings.add_target(ELECTRICITY.to_string(), 480.0);
+    target_settings.add_sources(super::solve::sources_set("basic").unwrap());
+
+    let recipe_set = plant_recipes("nuclear", "2x2").unwrap();
+    let solution = recipe_solver(&target_settings, recipe_set)
+        .unwrap()
+        .solve()
+        .unwrap();
+
+    // 2x2 reactors make 120 MW each with the neighbour bonus.
+    assert_eq!(solution.graph.producer("heat").unwrap().processer_num, 4);
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+use failure::Error;
+
+use super::solve::{apply_target_args, solver, solver_args};
+use super::SubCmd;
+use crate::formatter::formatter_by_name;
+use crate::research::{self, Labs};
+use crate::target::TargetSettings;
+
+pub struct Research();
+
+impl Research {
+    pub fn new() -> Research {
+        Research()
+    }
+}
+
+impl SubCmd for Research {
+    fn name(&self) -> &'static str {
+        "research"
+    }
+
+    fn command_args(&self) -> App<'static, 'static> {
+        solver_args(SubCommand::with_name(self.name()))
+            .about("Plan labs for a technology and solve its science packs.")
+            .arg(
+                Arg::with_name("units")
+                    .long("units")
+                    .short("u")
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("unit-time")
+                    .long("unit-time")
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("pack")
+                    .long("pack")
+                    .short("p")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("lab-speed")
+                    .long("lab-speed")
+                    .takes_value(true)
+                    .default_value("0"),
+            )
+            .arg(
+                Arg::with_name("lab-prod")
+                    .long("lab-prod")
+                    .takes_value(true)
+                    .default_value("0"),
+            )
+            .arg(Arg::with_name("time").long("time").takes_value(true))
+            .arg(Arg::with_name("labs").long("labs").takes_value(true))
+            .group(
+                ArgGroup::with_name("goal")
+                    .args(&["time", "labs"])
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .short("f")
+                    .default_value("text"),
+            )
+    }
+
+    fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
+        let mut ingredients = BTreeMap::new();
+        for p in matches.values_of("pack").unwrap() {
+            let (name, count) = research::Research::parse_ingredient(p)?;
+            *ingredients.entry(name).or_insert(0.0) += count;
+        }
+        let technology = research::Research {
+            units: matches.value_of("units").unwrap().parse()?,
+            ingredients,
+            unit_time: matches.value_of("unit-time").unwrap().parse()?,
+        };
+        let labs = Labs {
+            speed: matches.value_of("lab-speed").unwrap().parse()?,
+            productivity: matches.value_of("lab-prod").unwrap().parse()?,
+        };
+
+        let (lab_num, seconds) = if let Some(time) = matches.value_of("time") {
+            let seconds: f64 = time.parse()?;
+            (technology.labs_for(labs, seconds)?, seconds)
+        } else {
+            let lab_num: u64 = matches.value_of("labs").unwrap().parse()?;
+            (lab_num, technology.time_for(labs, lab_num)?)
+        };
+
+        println!(
+            "Units: {}, Unit time: {} s, Lab speed: {}%, Lab productivity: {}%",
+            technology.units, technology.unit_time, labs.speed, labs.productivity
+        );
+        println!("Labs: {}, Time: {:.1} s", lab_num, seconds);
+        println!();
+
+        let mut target_settings = TargetSettings::new();
+        for (name, rate) in technology.pack_rates(labs, seconds) {
+            target_settings.add_target(name, rate);
+        }
+        apply_target_args(matches, &mut target_settings, "basic")?;
+
+        let mut formatter = formatter_by_name(matches.value_of("format").unwrap())?;
+        formatter.format(&solver(&target_settings)?.solve()?)?;
+
+        Ok(())
+    }
+}
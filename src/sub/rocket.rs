@@ -0,0 +1,103 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure::{format_err, Error};
+
+use super::solve::{apply_target_args, solver, solver_args};
+use super::SubCmd;
+use crate::formatter::formatter_by_name;
+use crate::recipe::load_recipes;
+use crate::solution::Solution;
+use crate::target::TargetSettings;
+
+const ROCKET_PART: &str = "rocket-part";
+
+pub struct Rocket();
+
+impl Rocket {
+    pub fn new() -> Rocket {
+        Rocket()
+    }
+}
+
+impl SubCmd for Rocket {
+    fn name(&self) -> &'static str {
+        "rocket"
+    }
+
+    fn command_args(&self) -> App<'static, 'static> {
+        solver_args(SubCommand::with_name(self.name()))
+            .about("Plan rocket silos for launches per minute.")
+            .arg(
+                Arg::with_name("launches")
+                    .long("launches")
+                    .short("l")
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .short("f")
+                    .default_value("text"),
+            )
+    }
+
+    fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
+        let launches_per_min: f64 = matches.value_of("launches").unwrap().parse()?;
+        let launches_per_sec = launches_per_min / 60.0;
+
+        let recipe_set = load_recipes("./data/recipes")?;
+        let launch = recipe_set
+            .recipes()
+            .find(|r| r.ingredient_num(ROCKET_PART) > 0.0)
+            .ok_or_else(|| format_err!("no recipe launching {}s", ROCKET_PART))?;
+        let overhead = recipe_set
+            .find_recipes(ROCKET_PART)
+            .first()
+            .map_or(0.0, |r| r.overhead());
+
+        let mut target_settings = TargetSettings::new();
+        for (name, count) in launch.results() {
+            target_settings.add_target(name.clone(), count * launches_per_sec);
+        }
+        apply_target_args(matches, &mut target_settings, "basic")?;
+
+        let solution = solver(&target_settings)?.solve()?;
+
+        println!(
+            "Launches: {}/min, {} {}s per launch",
+            launches_per_min,
+            launch.ingredient_num(ROCKET_PART),
+            ROCKET_PART
+        );
+        print_silos(&solution, overhead, launches_per_sec);
+        println!();
+
+        let mut formatter = formatter_by_name(matches.value_of("format").unwrap())?;
+        formatter.format(&solution)?;
+
+        Ok(())
+    }
+}
+
+/// Prints silos building parts and launching, the launch is in the overhead of parts.
+fn print_silos(solution: &Solution, overhead: f64, launches_per_sec: f64) {
+    let (busy, part_per_sec, silo, silos) = match solution.graph.producer(ROCKET_PART) {
+        Some(r) => (
+            r.machines,
+            r.output.throughput(),
+            r.processer.name().to_string(),
+            r.processer_num,
+        ),
+        None => (0.0, 0.0, "-".to_string(), 0),
+    };
+    let launching = overhead * part_per_sec;
+
+    println!("Rocket parts: {:.2}/s ({})", part_per_sec, silo);
+    println!(
+        "Silos: {} ({:.2} building parts, {:.2} launching), a silo takes {:.1} s per launch",
+        silos,
+        busy - launching,
+        launching,
+        busy / launches_per_sec
+    );
+}
@@ -3,13 +3,20 @@ use failure::{format_err, Error};
 
 use crate::consts::BELT_THROUGHPUT;
 use crate::formatter::formatter_by_name;
+use crate::fuel::load_fuel_dict;
 use crate::processer;
-use crate::recipe::load_recipes;
-use crate::solver;
+use crate::recipe::{load_recipes, RecipeSet};
 use crate::solver::Solver;
-use crate::target::{load_target_settings, TargetSettings};
+use crate::source_set::load_source_sets;
+use crate::target::{
+    load_target_settings, parse_named_rate, parse_pin, SolverSettings, TargetSettings,
+};
+use crate::technology::load_tech_tree;
 
-use super::SubCmd;
+use super::{apply_processer_choice, apply_switch, processer_choice_args, switch_args, SubCmd};
+
+#[cfg(test)]
+mod tests;
 
 pub struct Solve();
 
@@ -25,145 +32,283 @@ impl SubCmd for Solve {
     }
 
     fn command_args(&self) -> App<'static, 'static> {
-        SubCommand::with_name(self.name())
-            .arg(
-                Arg::with_name("mult")
-                    .long("mult")
-                    .short("m")
-                    .takes_value(true),
-            )
-            .arg(Arg::with_name("all-merged").long("all-merged"))
-            .arg(
-                Arg::with_name("merged")
-                    .long("merged")
-                    .short("M")
-                    .multiple(true)
-                    .number_of_values(1)
-                    .takes_value(true),
-            )
-            .arg(
-                Arg::with_name("never-merged")
-                    .long("never-merged")
-                    .short("N")
-                    .multiple(true)
-                    .number_of_values(1)
-                    .takes_value(true),
-            )
-            .arg(
-                Arg::with_name("source-set")
-                    .long("source-set")
-                    .short("S")
-                    .takes_value(true),
-            )
-            .arg(
-                Arg::with_name("source")
-                    .long("source")
-                    .short("s")
-                    .multiple(true)
-                    .number_of_values(1)
-                    .takes_value(true),
-            )
-            .arg(Arg::with_name("no-beacon").long("no-beacon"))
-            .arg(Arg::with_name("no-speed").long("no-speed"))
-            .arg(Arg::with_name("no-prod").long("no-prod"))
-            .arg(Arg::with_name("allow-speed-only-beacon").long("allow-speed-only-beacon"))
-            .arg(
-                Arg::with_name("format")
-                    .long("format")
-                    .short("f")
-                    .default_value("text"),
-            )
-            .arg(Arg::with_name("target"))
+        solve_args(SubCommand::with_name(self.name())).arg(
+            Arg::with_name("format")
+                .long("format")
+                .short("f")
+                .default_value("text"),
+        )
     }
 
     fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
-        let target_str = matches
-            .value_of("target")
-            .ok_or_else(|| format_err!("target required."))?;
+        let target_settings = target_settings(matches)?;
+        let solver = solver(&target_settings)?;
+
+        let mut formatter = formatter_by_name(matches.value_of("format").unwrap())?;
+
+        let solution = solver.solve()?;
+
+        formatter.format(&solution)?;
+
+        Ok(())
+    }
+}
+
+/// Adds the arguments to build a solver for a target to `app`.
+pub fn solve_args(app: App<'static, 'static>) -> App<'static, 'static> {
+    solver_args(app)
+        .arg(
+            Arg::with_name("mult")
+                .long("mult")
+                .short("m")
+                .takes_value(true),
+        )
+        .arg(Arg::with_name("target"))
+}
+
+/// Adds the arguments to build a solver to `app`, for subcommands deriving their own
+/// targets.
+pub fn solver_args(app: App<'static, 'static>) -> App<'static, 'static> {
+    let app = switch_args(app, "all-merged", "no-all-merged");
+    let app = switch_args(app, "balance-oil", "no-balance-oil");
+    let app = switch_args(app, "no-remainder", "remainder");
+
+    let app = app
+        .arg(Arg::with_name("auto-merge").long("auto-merge"))
+        .arg(
+            Arg::with_name("auto-merge-consumers")
+                .long("auto-merge-consumers")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("auto-merge-belts")
+                .long("auto-merge-belts")
+                .takes_value(true),
+        )
+        .arg(Arg::with_name("fuel").long("fuel").takes_value(true))
+        .arg(
+            Arg::with_name("mining-prod")
+                .long("mining-prod")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("merged")
+                .long("merged")
+                .short("M")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("never-merged")
+                .long("never-merged")
+                .short("N")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("source-set")
+                .long("source-set")
+                .short("S")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("source")
+                .long("source")
+                .short("s")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("supply")
+                .long("supply")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("source-limit")
+                .long("source-limit")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pin")
+                .long("pin")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("researched")
+                .long("researched")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        );
+
+    processer_choice_args(app)
+}
+
+pub fn target_settings(matches: &ArgMatches) -> Result<TargetSettings, Error> {
+    let target_str = matches
+        .value_of("target")
+        .ok_or_else(|| format_err!("target required."))?;
 
-        let from_file = target_str.ends_with(".yaml") || target_str.ends_with(".yml");
+    let from_file = target_str.ends_with(".yaml") || target_str.ends_with(".yml");
 
-        let mut target_settings = if from_file {
-            load_target_settings(&target_str)
+    let mut target_settings = if from_file {
+        load_target_settings(&target_str)?
+    } else {
+        let mut tgt = TargetSettings::new();
+        tgt.add_target(target_str.to_string(), 1.0);
+        tgt
+    };
+
+    if let Some(mult) = matches.value_of("mult") {
+        if mult.ends_with('B') {
+            let mult = mult.trim_end_matches('B');
+            target_settings.multiply(BELT_THROUGHPUT * mult.parse::<f64>()?);
         } else {
-            let mut tgt = TargetSettings::new();
-            tgt.add_target(target_str.to_string(), 1.0);
-            tgt
-        };
-
-        let default_source_set = if from_file { "none" } else { "basic" };
-        let source_set = sources_set(matches.value_of("source-set").unwrap_or(default_source_set))?;
-        target_settings.add_sources(source_set);
-
-        if let Some(additional_sources) = matches.values_of("source") {
-            for s in additional_sources {
-                target_settings.add_source(s.to_string());
-            }
+            target_settings.multiply(mult.parse::<f64>()?);
         }
+    }
 
-        if let Some(mult) = matches.value_of("mult") {
-            if mult.ends_with('B') {
-                let mult = mult.trim_end_matches('B');
-                target_settings.multiply(BELT_THROUGHPUT * mult.parse::<f64>()?);
-            } else {
-                target_settings.multiply(mult.parse::<f64>()?);
-            }
-        }
+    let default_source_set = if from_file { "none" } else { "basic" };
+    apply_target_args(matches, &mut target_settings, default_source_set)?;
 
-        if let Some(mergeds) = matches.values_of("merged") {
-            target_settings.add_mergeds(mergeds.map(ToString::to_string).collect());
-        }
+    Ok(target_settings)
+}
 
-        let processer_choice = solver::ProcesserChoice::new()
-            .beacon(!matches.is_present("no-beacon"))
-            .speed_module(!matches.is_present("no-speed"))
-            .productivity_module(!matches.is_present("no-prod"))
-            .speed_only_beacon(matches.is_present("allow-speed-only-beacon"));
+/// Applies the arguments of `solver_args` to `target_settings`.
+pub fn apply_target_args(
+    matches: &ArgMatches,
+    target_settings: &mut TargetSettings,
+    default_source_set: &str,
+) -> Result<(), Error> {
+    let source_set = sources_set(matches.value_of("source-set").unwrap_or(default_source_set))?;
+    target_settings.add_sources(source_set);
 
-        let processer_set = processer::ProcSet::open_set()?;
+    if let Some(additional_sources) = matches.values_of("source") {
+        for s in additional_sources {
+            target_settings.add_source(s.to_string());
+        }
+    }
 
-        let mut solver = Solver::new(
-            load_recipes("./data/recipes")?,
-            &target_settings,
-            processer_set,
-            processer_choice,
-        );
+    if let Some(supplies) = matches.values_of("supply") {
+        for s in supplies {
+            let (name, rate) = parse_named_rate(s)?;
+            target_settings.add_supply(name, rate);
+        }
+    }
 
-        solver.all_merged(matches.is_present("all-merged"));
-        if let Some(never_merged) = matches.values_of("never-merged") {
-            solver.never_merged(never_merged);
+    if let Some(limits) = matches.values_of("source-limit") {
+        for l in limits {
+            let (name, rate) = parse_named_rate(l)?;
+            target_settings.set_source_limit(name, rate);
         }
+    }
 
-        let mut formatter = formatter_by_name(matches.value_of("format").unwrap())?;
+    if let Some(mergeds) = matches.values_of("merged") {
+        target_settings.add_mergeds(mergeds.map(ToString::to_string).collect());
+    }
 
-        let solution = solver.solve()?;
+    apply_solver_args(matches, target_settings.solver_settings_mut())
+}
 
-        formatter.format(&solution)?;
+pub fn solver(target_settings: &TargetSettings) -> Result<Solver, Error> {
+    recipe_solver(target_settings, load_recipes("./data/recipes")?)
+}
 
-        Ok(())
+/// Builds a solver like `solver`, but with `recipe_set` instead of the recipes on disk.
+pub fn recipe_solver(
+    target_settings: &TargetSettings,
+    mut recipe_set: RecipeSet,
+) -> Result<Solver, Error> {
+    let mut oil_recipe_set = load_recipes("./data/oil")?;
+    let mut processer_set = processer::ProcSet::open_set()?;
+    if let Some(researched) = &target_settings.solver_settings().researched {
+        let unlocks = load_tech_tree("./data/technologies.yaml")?.unlocks(researched)?;
+        unlocks.filter_recipes(&mut recipe_set);
+        unlocks.filter_recipes(&mut oil_recipe_set);
+        unlocks.filter_processers(&mut processer_set);
     }
+
+    let mut solver = Solver::new(recipe_set, target_settings, processer_set);
+    solver.fuel_dict(load_fuel_dict("./data/fuel.yaml")?);
+    solver.oil_recipe_set(oil_recipe_set);
+
+    Ok(solver)
 }
 
-fn sources_set(name: &str) -> Result<Vec<String>, Error> {
-    match name {
-        "none" => Ok(vec![]),
-        "basic" => Ok(vec![
-            "coal".to_string(),
-            "copper-plate".to_string(),
-            "iron-plate".to_string(),
-            "plastic-bar".to_string(),
-            "solid-fuel".to_string(),
-            "steel".to_string(),
-            "stone".to_string(),
-            "water".to_string(),
-            "crude-oil".to_string(),
-            "heavy-oil".to_string(),
-            "light-oil".to_string(),
-            "petroleum-gas".to_string(),
-            "lubricant".to_string(),
-            "sulfuric-acid".to_string(),
-            "uranium-235".to_string(),
-        ]),
-        _ => Err(format_err!("unknown source set: {}", name)),
+/// Overrides solver settings of the target file by the flags given.
+fn apply_solver_args(matches: &ArgMatches, settings: &mut SolverSettings) -> Result<(), Error> {
+    apply_processer_choice(matches, settings)?;
+
+    apply_switch(
+        matches,
+        &mut settings.all_merged,
+        "all-merged",
+        "no-all-merged",
+    );
+    if let Some(never_merged) = matches.values_of("never-merged") {
+        settings.never_merged = never_merged.map(ToString::to_string).collect();
+    }
+
+    apply_switch(
+        matches,
+        &mut settings.balance_oil,
+        "balance-oil",
+        "no-balance-oil",
+    );
+    apply_switch(
+        matches,
+        &mut settings.no_remainder,
+        "no-remainder",
+        "remainder",
+    );
+    if let Some(fuel) = matches.value_of("fuel") {
+        settings.fuel = Some(fuel.to_string());
+    }
+    if let Some(bonus) = matches.value_of("mining-prod") {
+        settings.mining_productivity = bonus.parse()?;
     }
+
+    if let Some(researched) = matches.values_of("researched") {
+        let all = settings.researched.get_or_insert_with(Vec::new);
+        for r in researched {
+            if !all.iter().any(|a| a == r) {
+                all.push(r.to_string());
+            }
+        }
+    }
+
+    if let Some(pins) = matches.values_of("pin") {
+        for p in pins {
+            let (name, pin) = parse_pin(p)?;
+            settings.pinned.insert(name, pin);
+        }
+    }
+
+    let consumers = matches.value_of("auto-merge-consumers");
+    let belts = matches.value_of("auto-merge-belts");
+    if matches.is_present("auto-merge") || consumers.is_some() || belts.is_some() {
+        let mut auto_merge = settings.auto_merge.take().unwrap_or_default();
+        if let Some(consumers) = consumers {
+            auto_merge.consumers = consumers.parse()?;
+        }
+        if let Some(belts) = belts {
+            auto_merge.belts = belts.parse()?;
+        }
+        settings.auto_merge = Some(auto_merge);
+    }
+
+    Ok(())
+}
+
+pub fn sources_set(name: &str) -> Result<Vec<String>, Error> {
+    load_source_sets("./data/source-sets.yaml")?.resolve(name)
 }
@@ -0,0 +1,61 @@
+use super::*;
+
+fn file_settings() -> SolverSettings {
+    SolverSettings {
+        no_beacon: true,
+        no_speed: true,
+        no_prod: true,
+        allow_speed_only_beacon: true,
+        all_merged: true,
+        balance_oil: true,
+        no_remainder: true,
+        ..SolverSettings::default()
+    }
+}
+
+fn apply(args: &[&str]) -> SolverSettings {
+    let matches = solve_args(SubCommand::with_name("solve")).get_matches_from(args);
+    let mut settings = file_settings();
+    apply_solver_args(&matches, &mut settings).unwrap();
+    settings
+}
+
+#[test]
+fn flags_should_turn_off_settings_of_the_file() {
+    let settings = apply(&[
+        "solve",
+        "--beacon",
+        "--speed",
+        "--prod",
+        "--no-speed-only-beacon",
+        "--no-all-merged",
+        "--no-balance-oil",
+        "--remainder",
+    ]);
+
+    assert!(!settings.no_beacon);
+    assert!(!settings.no_speed);
+    assert!(!settings.no_prod);
+    assert!(!settings.allow_speed_only_beacon);
+    assert!(!settings.all_merged);
+    assert!(!settings.balance_oil);
+    assert!(!settings.no_remainder);
+}
+
+#[test]
+fn settings_of_the_file_should_be_kept_without_flags() {
+    let settings = apply(&["solve", "--no-beacon"]);
+
+    assert!(settings.no_beacon);
+    assert!(settings.no_speed);
+    assert!(settings.all_merged);
+    assert!(settings.balance_oil);
+    assert!(settings.no_remainder);
+}
+
+#[test]
+fn opposite_flags_should_conflict() {
+    let args = ["solve", "--prod", "--no-prod"];
+    let matches = solve_args(SubCommand::with_name("solve")).get_matches_from_safe(args);
+    assert!(matches.is_err());
+}
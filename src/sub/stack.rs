@@ -6,13 +6,17 @@ use failure::{format_err, Error};
 use crate::consts::LIQUID_EQ_STACK_SIZE;
 use crate::near_name::NameSet;
 use crate::processer::ProcSet;
-use crate::recipe::load_recipes;
-use crate::solution::Throughput;
-use crate::solver::{ProcesserChoice, Solver};
-use crate::stack::load_stack_dict;
-use crate::target::TargetSettings;
+use crate::recipe::{load_recipes, RecipeSet};
+use crate::solution::{Missing, Throughput};
+use crate::solver::Solver;
+use crate::stack::{load_stack_dict, StackDict};
+use crate::target::{SolverSettings, TargetSettings};
 
-use super::SubCmd;
+use super::solve::sources_set;
+use super::{apply_processer_choice, processer_choice_args, SubCmd};
+
+#[cfg(test)]
+mod tests;
 
 pub struct Stack();
 
@@ -28,72 +32,231 @@ impl SubCmd for Stack {
     }
 
     fn command_args(&self) -> App<'static, 'static> {
-        SubCommand::with_name(self.name())
+        let app = SubCommand::with_name(self.name())
             .about("Calculate stack efficiency.")
-            .arg(Arg::with_name("no-prod").long("no-prod"))
-            .arg(Arg::with_name("target-name").required(true))
+            .arg(
+                Arg::with_name("all")
+                    .long("all")
+                    .short("a")
+                    .conflicts_with("target-name"),
+            )
+            .arg(
+                Arg::with_name("source-set")
+                    .long("source-set")
+                    .short("S")
+                    .takes_value(true)
+                    .default_value("basic"),
+            )
+            .arg(
+                Arg::with_name("source")
+                    .long("source")
+                    .short("s")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true),
+            )
+            .arg(Arg::with_name("target-name").required_unless("all"));
+
+        processer_choice_args(app)
     }
 
     fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
-        let use_prod = !matches.is_present("no-prod");
-        let target = matches.value_of("target-name").unwrap();
-
         let stack_dict = load_stack_dict("./data/stack-size.yaml")?;
         let recipe_set = load_recipes("./data/recipes")?;
+        let oil_recipe_set = load_recipes("./data/oil")?;
+        let processer_set = ProcSet::open_set()?;
+        let mut solver_settings = SolverSettings::default();
+        apply_processer_choice(matches, &mut solver_settings)?;
+
+        let mut sources = sources_set(matches.value_of("source-set").unwrap())?;
+        if let Some(additional_sources) = matches.values_of("source") {
+            sources.extend(additional_sources.map(ToString::to_string));
+        }
+
+        let calc = StackCalc {
+            stack_dict: &stack_dict,
+            recipe_set: &recipe_set,
+            oil_recipe_set: &oil_recipe_set,
+            processer_set: &processer_set,
+            solver_settings: &solver_settings,
+            sources: &sources,
+        };
+
+        if matches.is_present("all") {
+            return rank_all(&calc);
+        }
+
+        let target = matches.value_of("target-name").unwrap();
 
         let mut name_set = NameSet::new();
         name_set.add_names(stack_dict.names());
         name_set.add_names(recipe_set.all_results());
 
-        let mut target_settings = TargetSettings::new();
-
-        let target_stack_size = stack_dict.get(target).ok_or_else(|| {
+        if stack_dict.get(target).is_none() {
             let candidates = name_set.find_nearest_names(target, 3);
-            format_err!(
+            return Err(format_err!(
                 "unknown stack size: {}, Did you mean: {:?}?",
                 target,
                 candidates
-            )
-        })?;
-        let target_stack_size = target_stack_size as f64;
-        target_settings.add_target(target.to_string(), target_stack_size);
-        target_settings.add_sources(vec![
-            "coal".to_string(),
-            "copper-plate".to_string(),
-            "iron-plate".to_string(),
-            "plastic-bar".to_string(),
-            "solid-fuel".to_string(),
-            "steel".to_string(),
-            "stone".to_string(),
-            "water".to_string(),
-            "crude-oil".to_string(),
-            "heavy-oil".to_string(),
-            "light-oil".to_string(),
-            "petroleum-gas".to_string(),
-            "lubricant".to_string(),
-            "sulfuric-acid".to_string(),
-        ]);
-
-        let processer_choice = ProcesserChoice::new().productivity_module(use_prod);
-        let processer_set = ProcSet::open_set()?;
-
-        let mut solver = Solver::new(
-            recipe_set,
-            &target_settings,
-            processer_set,
-            processer_choice,
-        );
+            ));
+        }
 
-        let solution = solver.solve()?;
+        let efficiency = calc.efficiency(target)?;
 
-        if !solution.missings.is_empty() {
+        if !efficiency.missings.is_empty() {
             eprintln!("Missing items:");
-            for m in &solution.missings {
+            for m in &efficiency.missings {
                 eprintln!("    {}", m);
             }
             eprintln!();
         }
 
+        if !efficiency.source_stacks.is_empty() {
+            println!("Source stacks:");
+            for (n, s) in &efficiency.source_stacks {
+                println!("    {}: {:.2} st", n, s);
+            }
+        }
+
+        if !efficiency.liquids.is_empty() {
+            println!();
+            println!("Liquids:");
+            for (n, a) in &efficiency.liquids {
+                println!(
+                    "    {}: {:.1} (eq {:.1} st)",
+                    n,
+                    a,
+                    a / LIQUID_EQ_STACK_SIZE
+                );
+            }
+        }
+
+        println!();
+        println!("Total: {:.2} st", efficiency.total_stacks);
+        println!("Efficiency: {:.0}%", efficiency.percent());
+
+        Ok(())
+    }
+}
+
+/// Fluids made and used on site, never shipped.
+const ON_SITE_FLUIDS: [&str; 2] = ["steam", "high-temperature-steam"];
+
+fn rank_all(calc: &StackCalc) -> Result<(), Error> {
+    let ranking = rank(calc);
+
+    println!(
+        "| {:^4} | {:^32} | {:^12} |",
+        "rank", "item", "efficiency %"
+    );
+    println!("|-----:|:---------------------------------|-------------:|");
+    for (i, (name, percent)) in ranking.ranked.iter().enumerate() {
+        println!("| {:>4} | {:<32} | {:>12.0} |", i + 1, name, percent);
+    }
+
+    if !ranking.skipped.is_empty() {
+        eprintln!();
+        eprintln!("Skipped items with missing sources:");
+        for n in &ranking.skipped {
+            eprintln!("    {}", n);
+        }
+    }
+
+    if !ranking.failed.is_empty() {
+        eprintln!();
+        eprintln!("Failed items:");
+        for (n, err) in &ranking.failed {
+            eprintln!("    {}: {}", n, err);
+        }
+    }
+
+    Ok(())
+}
+
+struct Ranking<'a> {
+    ranked: Vec<(&'a str, f64)>,
+    skipped: Vec<&'a str>,
+    failed: Vec<(&'a str, Error)>,
+}
+
+/// Ranks the crafted items by efficiency, leaving out raw resources and sources.
+fn rank<'a>(calc: &StackCalc<'a>) -> Ranking<'a> {
+    let mut ranking = Ranking {
+        ranked: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for name in calc.stack_dict.names() {
+        let recipes = calc.recipe_set.find_recipes(name);
+        if recipes.iter().all(|r| r.is_mining())
+            || ON_SITE_FLUIDS.contains(&name)
+            || calc.sources.iter().any(|s| s == name)
+        {
+            continue;
+        }
+
+        match calc.efficiency(name) {
+            Ok(efficiency) if efficiency.missings.is_empty() => {
+                ranking.ranked.push((name, efficiency.percent()))
+            }
+            Ok(_) => ranking.skipped.push(name),
+            Err(err) => ranking.failed.push((name, err)),
+        }
+    }
+
+    ranking
+        .ranked
+        .sort_by(|l, r| r.1.partial_cmp(&l.1).unwrap().then(l.0.cmp(r.0)));
+    ranking.skipped.sort();
+    ranking.failed.sort_by(|l, r| l.0.cmp(r.0));
+    ranking
+}
+
+struct StackCalc<'a> {
+    stack_dict: &'a StackDict,
+    recipe_set: &'a RecipeSet,
+    oil_recipe_set: &'a RecipeSet,
+    processer_set: &'a ProcSet,
+    solver_settings: &'a SolverSettings,
+    sources: &'a [String],
+}
+
+struct StackEfficiency {
+    total_stacks: f64,
+    source_stacks: BTreeMap<String, f64>,
+    liquids: BTreeMap<String, f64>,
+    missings: Vec<Missing>,
+}
+
+impl StackEfficiency {
+    fn percent(&self) -> f64 {
+        100.0 * self.total_stacks
+    }
+}
+
+impl<'a> StackCalc<'a> {
+    /// Source stacks needed to make one stack of `target`.
+    fn efficiency(&self, target: &str) -> Result<StackEfficiency, Error> {
+        let target_stack_size = self
+            .stack_dict
+            .get(target)
+            .ok_or_else(|| format_err!("unknown stack size: {}", target))?;
+
+        let mut target_settings = TargetSettings::new();
+        target_settings.add_target(target.to_string(), target_stack_size as f64);
+        target_settings.add_sources(self.sources.to_vec());
+        *target_settings.solver_settings_mut() = self.solver_settings.clone();
+
+        let mut solver = Solver::new(
+            self.recipe_set.clone(),
+            &target_settings,
+            self.processer_set.clone(),
+        );
+        solver.oil_recipe_set(self.oil_recipe_set.clone());
+
+        let solution = solver.solve()?;
+
         let mut total_stacks = 0f64;
         let mut source_stacks = BTreeMap::new();
         let mut liquids = BTreeMap::new();
@@ -101,7 +264,8 @@ impl SubCmd for Stack {
         for src in &solution.sources {
             match src {
                 Throughput::Item(n, t) => {
-                    let stack_size = stack_dict
+                    let stack_size = self
+                        .stack_dict
                         .get(n)
                         .ok_or_else(|| format_err!("unknown stack size: {}", n))?;
                     let stacks = t / (stack_size as f64);
@@ -112,33 +276,15 @@ impl SubCmd for Stack {
                     liquids.insert(n.to_string(), *t);
                     total_stacks += *t / LIQUID_EQ_STACK_SIZE;
                 }
+                Throughput::Power(_, _) => {}
             }
         }
 
-        if !source_stacks.is_empty() {
-            println!("Source stacks:");
-            for (n, s) in source_stacks {
-                println!("    {}: {:.2} st", n, s);
-            }
-        }
-
-        if !liquids.is_empty() {
-            println!();
-            println!("Liquids:");
-            for (n, a) in liquids {
-                println!(
-                    "    {}: {:.1} (eq {:.1} st)",
-                    n,
-                    a,
-                    a / LIQUID_EQ_STACK_SIZE
-                );
-            }
-        }
-
-        println!();
-        println!("Total: {:.2} st", total_stacks);
-        println!("Efficiency: {:.0}%", 100.0 * total_stacks);
-
-        Ok(())
+        Ok(StackEfficiency {
+            total_stacks,
+            source_stacks,
+            liquids,
+            missings: solution.missings,
+        })
     }
 }
@@ -0,0 +1,52 @@
+use super::*;
+
+fn ranking_names(max_tier: Option<u32>) -> (Vec<String>, Vec<String>) {
+    let stack_dict = load_stack_dict("./data/stack-size.yaml").unwrap();
+    let recipe_set = load_recipes("./data/recipes").unwrap();
+    let oil_recipe_set = load_recipes("./data/oil").unwrap();
+    let processer_set = ProcSet::open_set().unwrap();
+    let solver_settings = SolverSettings {
+        max_tier,
+        ..SolverSettings::default()
+    };
+    let sources = sources_set("basic").unwrap();
+
+    let calc = StackCalc {
+        stack_dict: &stack_dict,
+        recipe_set: &recipe_set,
+        oil_recipe_set: &oil_recipe_set,
+        processer_set: &processer_set,
+        solver_settings: &solver_settings,
+        sources: &sources,
+    };
+    let ranking = rank(&calc);
+
+    let ranked = ranking.ranked.iter().map(|r| r.0.to_string()).collect();
+    let failed = ranking.failed.iter().map(|f| f.0.to_string()).collect();
+    (ranked, failed)
+}
+
+#[test]
+fn rank_should_leave_out_raw_resources_and_on_site_fluids() {
+    let (ranked, failed) = ranking_names(None);
+
+    assert!(failed.is_empty());
+    assert!(ranked.iter().any(|n| n == "iron-gear-wheel"));
+    for n in &[
+        "iron-ore",
+        "copper-ore",
+        "uranium-ore",
+        "steam",
+        "high-temperature-steam",
+    ] {
+        assert!(!ranked.iter().any(|r| r == n), "{}", n);
+    }
+}
+
+#[test]
+fn rank_should_go_on_after_failing_items() {
+    let (ranked, failed) = ranking_names(Some(1));
+
+    assert!(!failed.is_empty());
+    assert!(ranked.iter().any(|n| n == "iron-gear-wheel"));
+}
@@ -0,0 +1,134 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure::Error;
+
+use super::solve::{solve_args, solver, target_settings};
+use super::SubCmd;
+use crate::solution::Throughput;
+use crate::stack::load_stack_dict;
+use crate::transport::{transport, Train, Trains, Transport};
+
+pub struct TransportCmd();
+
+impl TransportCmd {
+    pub fn new() -> TransportCmd {
+        TransportCmd()
+    }
+}
+
+impl SubCmd for TransportCmd {
+    fn name(&self) -> &'static str {
+        "transport"
+    }
+
+    fn command_args(&self) -> App<'static, 'static> {
+        solve_args(SubCommand::with_name(self.name()))
+            .about("Compare shipping targets against their sources by train.")
+            .arg(
+                Arg::with_name("train")
+                    .long("train")
+                    .short("t")
+                    .takes_value(true)
+                    .default_value("1-4"),
+            )
+            .arg(
+                Arg::with_name("round-trip")
+                    .long("round-trip")
+                    .short("r")
+                    .takes_value(true)
+                    .default_value("120"),
+            )
+    }
+
+    fn exec(&self, matches: &ArgMatches) -> Result<(), Error> {
+        let train: Train = matches.value_of("train").unwrap().parse()?;
+        let round_trip: f64 = matches.value_of("round-trip").unwrap().parse()?;
+
+        let target_settings = target_settings(matches)?;
+        let solution = solver(&target_settings)?.solve()?;
+
+        if !solution.missings.is_empty() {
+            eprintln!("Missing items:");
+            for m in &solution.missings {
+                eprintln!("    {}", m);
+            }
+            eprintln!();
+        }
+
+        let stack_dict = load_stack_dict("./data/stack-size.yaml")?;
+
+        // Targets as solved, scaled when the solution was fitted to limits.
+        let mut targets: Vec<Throughput> = solution
+            .graph
+            .items
+            .iter()
+            .filter(|i| i.is_target())
+            .map(|i| Throughput::new(i.throughput.name().to_string(), i.target))
+            .collect();
+        targets.sort_by(|l, r| l.name().cmp(r.name()));
+
+        let products = transport(&stack_dict, &targets, train, round_trip)?;
+        let sources = transport(&stack_dict, &solution.sources, train, round_trip)?;
+
+        println!(
+            "Train: {}-{}, Round trip: {} s",
+            train.locomotives, train.wagons, round_trip
+        );
+
+        println!();
+        println!("Products:");
+        print_transport(&products, train);
+
+        println!();
+        println!("Sources:");
+        print_transport(&sources, train);
+
+        if products.wagons_per_min() > 0.0 {
+            println!();
+            println!(
+                "Sources need {:.0}% of the wagons of products.",
+                100.0 * sources.wagons_per_min() / products.wagons_per_min()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn print_transport(transport: &Transport, train: Train) {
+    println!(
+        "| {:^24} | {:^12} | {:^6} | {:^10} |",
+        "item", "amount/min", "wagon", "wagons/min"
+    );
+    println!("|:-------------------------|-------------:|:------:|-----------:|");
+
+    for s in &transport.shipments {
+        println!(
+            "| {:<24} | {:>12.1} | {:^6} | {:>10.2} |",
+            s.name,
+            s.per_min,
+            if s.liquid { "fluid" } else { "cargo" },
+            s.wagons_per_min,
+        );
+    }
+
+    println!();
+    print_trains("cargo", &transport.cargo, train);
+    print_trains("fluid", &transport.fluid, train);
+}
+
+fn print_trains(kind: &str, trains: &Trains, train: Train) {
+    if trains.wagons_per_min <= 0.0 {
+        return;
+    }
+
+    println!(
+        "    {}: {:.2} wagons/min, {:.2} trains/min, {} trains of {}-{} (capacity {:.2} wagons/min)",
+        kind,
+        trains.wagons_per_min,
+        trains.trains_per_min,
+        trains.trains,
+        train.locomotives,
+        train.wagons,
+        trains.capacity_per_min,
+    );
+}
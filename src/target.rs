@@ -1,9 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
 
+use failure::{format_err, Error};
 use serde::{Deserialize, Serialize};
 
+use crate::consts::BELT_THROUGHPUT;
+use crate::processer::ProcesserChoice;
+use crate::solver::AutoMerge;
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TargetSettings {
     targets: HashMap<String, f64>,
@@ -11,6 +22,14 @@ pub struct TargetSettings {
     sources: Vec<String>,
     #[serde(default)]
     merged: Vec<String>,
+    /// Limited supplies of sources, targets are scaled to fit them.
+    #[serde(default)]
+    supplies: Vec<Flow>,
+    /// Highest rates sources can be imported at, by name.
+    #[serde(default)]
+    source_limits: BTreeMap<String, f64>,
+    #[serde(default)]
+    solver: SolverSettings,
 }
 
 impl TargetSettings {
@@ -19,6 +38,9 @@ impl TargetSettings {
             targets: HashMap::new(),
             sources: Vec::new(),
             merged: Vec::new(),
+            supplies: Vec::new(),
+            source_limits: BTreeMap::new(),
+            solver: SolverSettings::default(),
         }
     }
 
@@ -30,17 +52,40 @@ impl TargetSettings {
     }
 
     pub fn add_source(&mut self, name: String) {
-        self.sources.push(name);
+        if !self.sources.contains(&name) {
+            self.sources.push(name);
+        }
     }
 
     pub fn add_sources(&mut self, names: Vec<String>) {
-        let mut names = names;
-        self.sources.append(&mut names);
+        for n in names {
+            self.add_source(n);
+        }
+    }
+
+    /// Adds a supply of `name`, which is used as a source.
+    pub fn add_supply(&mut self, name: String, throughput: f64) {
+        match self.supplies.iter_mut().find(|s| s.name == name) {
+            Some(s) => s.throughput += throughput,
+            None => self.supplies.push(Flow {
+                name: name.clone(),
+                throughput,
+            }),
+        }
+        self.add_source(name);
+    }
+
+    /// Limits the import of source `name`, replacing any previous limit.
+    pub fn set_source_limit(&mut self, name: String, throughput: f64) {
+        self.source_limits.insert(name, throughput);
     }
 
     pub fn add_mergeds(&mut self, names: Vec<String>) {
-        let mut names = names;
-        self.merged.append(&mut names);
+        for n in names {
+            if !self.merged.contains(&n) {
+                self.merged.push(n);
+            }
+        }
     }
 
     pub fn targets(&self) -> Vec<Flow> {
@@ -61,19 +106,330 @@ impl TargetSettings {
         &self.merged
     }
 
+    pub fn supplies(&self) -> &[Flow] {
+        &self.supplies
+    }
+
+    pub fn source_limits(&self) -> &BTreeMap<String, f64> {
+        &self.source_limits
+    }
+
+    pub fn solver_settings(&self) -> &SolverSettings {
+        &self.solver
+    }
+
+    pub fn solver_settings_mut(&mut self) -> &mut SolverSettings {
+        &mut self.solver
+    }
+
     pub fn multiply(&mut self, mult: f64) {
         self.targets.iter_mut().for_each(|(_, t)| *t *= mult);
     }
 }
 
+/// Settings of the solver, the same as the flags of `solve`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolverSettings {
+    pub no_beacon: bool,
+    pub no_speed: bool,
+    pub no_prod: bool,
+    pub allow_speed_only_beacon: bool,
+    /// Highest machine tier unlocked, all tiers when omitted.
+    pub max_tier: Option<u32>,
+    pub all_merged: bool,
+    pub never_merged: Vec<String>,
+    pub auto_merge: Option<AutoMerge>,
+    pub balance_oil: bool,
+    /// Report demand above source limits as shortfall instead of producing the rest.
+    pub no_remainder: bool,
+    /// Item burnt by burner processers, their fuel is ignored when omitted.
+    pub fuel: Option<String>,
+    /// Mining productivity research bonus in percent.
+    pub mining_productivity: f64,
+    /// Researched technologies, everything is unlocked when omitted.
+    pub researched: Option<Vec<String>>,
+    /// Processer settings for single items or recipes, by name.
+    pub overrides: BTreeMap<String, ProcesserOverride>,
+    /// Fixed builds of items, targets are scaled to what they make.
+    pub pinned: BTreeMap<String, Pin>,
+}
+
+/// A fixed number of machines of one processer variant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pin {
+    pub processer: String,
+    pub count: u64,
+}
+
+/// Processer settings replacing the global ones for an item or a recipe.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcesserOverride {
+    pub no_beacon: Option<bool>,
+    pub no_speed: Option<bool>,
+    pub no_prod: Option<bool>,
+    pub allow_speed_only_beacon: Option<bool>,
+    pub max_tier: Option<u32>,
+    pub machine: Option<String>,
+    pub processer: Option<String>,
+}
+
+impl ProcesserOverride {
+    pub fn apply(&self, choice: &ProcesserChoice) -> ProcesserChoice {
+        let mut choice = choice.clone();
+
+        if let Some(no_beacon) = self.no_beacon {
+            choice = choice.beacon(!no_beacon);
+        }
+        if let Some(no_speed) = self.no_speed {
+            choice = choice.speed_module(!no_speed);
+        }
+        if let Some(no_prod) = self.no_prod {
+            choice = choice.productivity_module(!no_prod);
+        }
+        if let Some(allow) = self.allow_speed_only_beacon {
+            choice = choice.speed_only_beacon(allow);
+        }
+        if self.max_tier.is_some() {
+            choice = choice.max_tier(self.max_tier);
+        }
+        if let Some(machine) = &self.machine {
+            choice = choice.machine(machine);
+        }
+        if let Some(processer) = &self.processer {
+            choice = choice.processer(processer);
+        }
+
+        choice
+    }
+}
+
+impl SolverSettings {
+    pub fn processer_choice(&self) -> ProcesserChoice {
+        ProcesserChoice::new()
+            .beacon(!self.no_beacon)
+            .speed_module(!self.no_speed)
+            .productivity_module(!self.no_prod)
+            .speed_only_beacon(self.allow_speed_only_beacon)
+            .max_tier(self.max_tier)
+    }
+
+    /// Merges settings of an included file, flags set anywhere stay set and
+    /// overrides of the same name are replaced.
+    fn merge(&mut self, other: SolverSettings) {
+        self.no_beacon |= other.no_beacon;
+        self.no_speed |= other.no_speed;
+        self.no_prod |= other.no_prod;
+        self.allow_speed_only_beacon |= other.allow_speed_only_beacon;
+        if other.max_tier.is_some() {
+            self.max_tier = other.max_tier;
+        }
+        self.all_merged |= other.all_merged;
+        for n in other.never_merged {
+            if !self.never_merged.contains(&n) {
+                self.never_merged.push(n);
+            }
+        }
+        if other.auto_merge.is_some() {
+            self.auto_merge = other.auto_merge;
+        }
+        self.balance_oil |= other.balance_oil;
+        self.no_remainder |= other.no_remainder;
+        if other.fuel.is_some() {
+            self.fuel = other.fuel;
+        }
+        self.mining_productivity = self.mining_productivity.max(other.mining_productivity);
+        if let Some(researched) = other.researched {
+            let all = self.researched.get_or_insert_with(Vec::new);
+            for r in researched {
+                if !all.contains(&r) {
+                    all.push(r);
+                }
+            }
+        }
+        self.overrides.extend(other.overrides);
+        self.pinned.extend(other.pinned);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Flow {
     pub name: String,
     pub throughput: f64,
 }
 
-pub fn load_target_settings(file_name: &str) -> TargetSettings {
-    let file = fs::File::open(file_name).expect("failed open file");
+/// Target file as written, before includes are resolved.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TargetFile {
+    #[serde(default)]
+    include: Vec<Include>,
+    #[serde(default)]
+    targets: HashMap<String, Rate>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    merged: Vec<String>,
+    #[serde(default)]
+    supplies: HashMap<String, Rate>,
+    #[serde(default)]
+    source_limits: HashMap<String, Rate>,
+    #[serde(default)]
+    solver: SolverSettings,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Include {
+    File(String),
+    Scaled { file: String, scale: f64 },
+}
+
+impl Include {
+    fn file(&self) -> &str {
+        match self {
+            Include::File(f) => f,
+            Include::Scaled { file, .. } => file,
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        match self {
+            Include::File(_) => 1.0,
+            Include::Scaled { scale, .. } => *scale,
+        }
+    }
+}
+
+/// Rate in items per second, written as a number or with a unit: `/s`, `/min` or belts (`B`).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Rate {
+    PerSec(f64),
+    Text(String),
+}
+
+impl Rate {
+    fn per_sec(&self) -> Result<f64> {
+        match self {
+            Rate::PerSec(r) => Ok(*r),
+            Rate::Text(t) => parse_rate(t),
+        }
+    }
+}
+
+pub fn parse_rate(s: &str) -> Result<f64> {
+    let s = s.trim();
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<f64>()
+            .map_err(|_| format_err!("invalid rate: {}", s))
+    };
+
+    if s.ends_with("/min") {
+        Ok(parse(s.trim_end_matches("/min"))? / 60.0)
+    } else if s.ends_with("/s") {
+        parse(s.trim_end_matches("/s"))
+    } else if s.ends_with('B') {
+        Ok(parse(s.trim_end_matches('B'))? * BELT_THROUGHPUT)
+    } else {
+        parse(s)
+    }
+}
+
+/// Parses a rate of an item written as `NAME=RATE`, e.g. `iron-plate=2B`.
+pub fn parse_named_rate(s: &str) -> Result<(String, f64)> {
+    let mut parts = s.splitn(2, '=');
+    let name = parts.next().unwrap_or("").trim();
+    let rate = parts
+        .next()
+        .ok_or_else(|| format_err!("rate must be like NAME=RATE: {}", s))?;
+    if name.is_empty() {
+        return Err(format_err!("rate must be like NAME=RATE: {}", s));
+    }
+
+    Ok((name.to_string(), parse_rate(rate)?))
+}
+
+/// Parses a pinned item written as `ITEM=PROCESSER:COUNT`, e.g. `iron-plate=furnace-p2:12`.
+pub fn parse_pin(s: &str) -> Result<(String, Pin)> {
+    let invalid = || format_err!("pin must be like ITEM=PROCESSER:COUNT: {}", s);
+
+    let mut parts = s.splitn(2, '=');
+    let name = parts.next().unwrap_or("").trim();
+    let mut pin = parts.next().ok_or_else(invalid)?.rsplitn(2, ':');
+    let count = pin.next().unwrap_or("").trim();
+    let processer = pin.next().ok_or_else(invalid)?.trim();
+    if name.is_empty() || processer.is_empty() {
+        return Err(invalid());
+    }
+
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    if count == 0 {
+        return Err(format_err!("pin needs machines: {}", s));
+    }
+
+    Ok((
+        name.to_string(),
+        Pin {
+            processer: processer.to_string(),
+            count,
+        },
+    ))
+}
+
+/// Loads target settings, merging included files into one.
+///
+/// Included targets are scaled and added up, supplies are added up unscaled, sources
+/// and merged items are combined, source limits of the including file win, the largest
+/// mining productivity wins and solver settings are merged.
+pub fn load_target_settings(file_name: &str) -> Result<TargetSettings> {
+    let mut settings = TargetSettings::new();
+    load_into(Path::new(file_name), 1.0, &mut Vec::new(), &mut settings)?;
+    Ok(settings)
+}
+
+fn load_into(
+    path: &Path,
+    scale: f64,
+    loading: &mut Vec<PathBuf>,
+    settings: &mut TargetSettings,
+) -> Result<()> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if loading.contains(&canonical) {
+        return Err(format_err!(
+            "target file includes itself: {}",
+            path.display()
+        ));
+    }
+
+    let file = fs::File::open(path)
+        .map_err(|e| format_err!("failed to open {}: {}", path.display(), e))?;
     let reader = BufReader::new(file);
-    serde_yaml::from_reader(reader).expect("can't parse target settings")
+    let target_file: TargetFile = serde_yaml::from_reader(reader)
+        .map_err(|e| format_err!("can't parse {}: {}", path.display(), e))?;
+
+    loading.push(canonical);
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    for i in &target_file.include {
+        load_into(&dir.join(i.file()), scale * i.scale(), loading, settings)?;
+    }
+    loading.pop();
+
+    for (n, r) in &target_file.targets {
+        settings.add_target(n.clone(), scale * r.per_sec()?);
+    }
+    for (n, r) in &target_file.supplies {
+        settings.add_supply(n.clone(), r.per_sec()?);
+    }
+    for (n, r) in &target_file.source_limits {
+        settings.set_source_limit(n.clone(), r.per_sec()?);
+    }
+    settings.add_sources(target_file.sources);
+    settings.add_mergeds(target_file.merged);
+    settings.solver.merge(target_file.solver);
+
+    Ok(())
 }
@@ -0,0 +1,98 @@
+use super::*;
+
+fn target(settings: &TargetSettings, name: &str) -> f64 {
+    settings
+        .targets()
+        .iter()
+        .find(|f| f.name == name)
+        .map(|f| f.throughput)
+        .unwrap_or(0.0)
+}
+
+#[test]
+fn parse_rate_should_accept_units() {
+    let params = [
+        ("1.5", 1.5),
+        ("2/s", 2.0),
+        ("30/min", 0.5),
+        ("2B", 2.0 * BELT_THROUGHPUT),
+        ("0.5 B", 0.5 * BELT_THROUGHPUT),
+    ];
+
+    for p in &params {
+        assert!((parse_rate(p.0).unwrap() - p.1).abs() < 1e-9, "{}", p.0);
+    }
+
+    assert!(parse_rate("fast").is_err());
+}
+
+#[test]
+fn parse_pin_should_read_processer_and_count() {
+    assert_eq!(
+        parse_pin("electronic-circuit=assembler-p4-b8:12").unwrap(),
+        (
+            "electronic-circuit".to_string(),
+            Pin {
+                processer: "assembler-p4-b8".to_string(),
+                count: 12,
+            }
+        )
+    );
+
+    assert!(parse_pin("electronic-circuit=12").is_err());
+    assert!(parse_pin("electronic-circuit=assembler:0").is_err());
+}
+
+#[test]
+fn science_should_include_each_science_pack() {
+    let settings = load_target_settings("./data/target/science.yaml").unwrap();
+
+    assert_eq!(settings.targets().len(), 6);
+    assert!((target(&settings, "automation-science-pack") - 1.0).abs() < 1e-9);
+    assert!((target(&settings, "military-science-pack") - 0.1).abs() < 1e-9);
+
+    // sources and merged items are combined across includes
+    assert!(settings.sources().contains(&"lubricant".to_string()));
+    assert!(settings
+        .sources()
+        .contains(&"electronic-circuit".to_string()));
+    assert!(settings.merged().contains(&"engine-unit".to_string()));
+    assert!(settings.merged().contains(&"stone-brick".to_string()));
+}
+
+#[test]
+fn solver_settings_should_merge_included_flags() {
+    let mut settings = SolverSettings {
+        no_beacon: true,
+        never_merged: vec!["iron-gear-wheel".to_string()],
+        mining_productivity: 10.0,
+        ..SolverSettings::default()
+    };
+    let included: SolverSettings = serde_yaml::from_str(
+        r#"
+no_prod: true
+never_merged: [copper-cable, iron-gear-wheel]
+researched: [automation]
+mining_productivity: 20
+auto_merge:
+  consumers: 3
+"#,
+    )
+    .unwrap();
+
+    settings.merge(included);
+
+    assert!(settings.no_beacon);
+    assert!(settings.no_prod);
+    assert!(!settings.no_speed);
+    assert_eq!(
+        settings.never_merged,
+        vec!["iron-gear-wheel", "copper-cable"]
+    );
+    assert_eq!(settings.researched, Some(vec!["automation".to_string()]));
+    assert!((settings.mining_productivity - 20.0).abs() < 1e-9);
+
+    let auto_merge = settings.auto_merge.unwrap();
+    assert_eq!(auto_merge.consumers, 3);
+    assert!((auto_merge.belts - 1.0).abs() < 1e-9);
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::BufReader;
+
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::near_name::NameSet;
+use crate::processer::{ProcSet, Processer};
+use crate::recipe::RecipeSet;
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Technology researched from the start of a game.
+pub const START: &str = "start";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Technology {
+    prerequisites: Vec<String>,
+    recipes: Vec<String>,
+    machines: Vec<String>,
+    /// Module kinds: `speed-module`, `productivity-module` or `beacon`.
+    modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TechTree(HashMap<String, Technology>);
+
+/// Recipes, machines and module kinds unlocked by researched technologies.
+#[derive(Debug, Default)]
+pub struct Unlocks {
+    recipes: HashSet<String>,
+    machines: HashSet<String>,
+    modules: HashSet<String>,
+}
+
+impl TechTree {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Recipe names unlocked by any technology.
+    pub fn all_recipes(&self) -> HashSet<&str> {
+        self.0
+            .values()
+            .flat_map(|t| t.recipes.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Returns what `researched`, their prerequisites and `start` unlock.
+    pub fn unlocks(&self, researched: &[String]) -> Result<Unlocks> {
+        let mut unlocks = Unlocks::default();
+        let mut done = HashSet::new();
+
+        self.unlock_into(START, &mut Vec::new(), &mut done, &mut unlocks)?;
+        for r in researched {
+            self.unlock_into(r, &mut Vec::new(), &mut done, &mut unlocks)?;
+        }
+
+        Ok(unlocks)
+    }
+
+    fn unlock_into(
+        &self,
+        name: &str,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        unlocks: &mut Unlocks,
+    ) -> Result<()> {
+        if path.iter().any(|p| p == name) {
+            return Err(format_err!(
+                "technology requires itself: {} -> {}",
+                path.join(" -> "),
+                name
+            ));
+        }
+        if done.contains(name) {
+            return Ok(());
+        }
+
+        let tech = self.0.get(name).ok_or_else(|| {
+            let mut name_set = NameSet::new();
+            name_set.add_names(self.names());
+            format_err!(
+                "unknown technology: {}, Did you mean: {:?}?",
+                name,
+                name_set.find_nearest_names(name, 3)
+            )
+        })?;
+
+        path.push(name.to_string());
+        for p in &tech.prerequisites {
+            self.unlock_into(p, path, done, unlocks)?;
+        }
+        path.pop();
+
+        unlocks.recipes.extend(tech.recipes.iter().cloned());
+        unlocks.machines.extend(tech.machines.iter().cloned());
+        unlocks.modules.extend(tech.modules.iter().cloned());
+        done.insert(name.to_string());
+
+        Ok(())
+    }
+}
+
+impl Unlocks {
+    /// Removes the recipes not unlocked yet from `recipe_set`.
+    pub fn filter_recipes(&self, recipe_set: &mut RecipeSet) {
+        recipe_set.retain(|r| self.recipes.contains(&r.name()));
+    }
+
+    /// Removes the machines and module configurations not unlocked yet from `processer_set`.
+    pub fn filter_processers(&self, processer_set: &mut ProcSet) {
+        processer_set.retain(|p| self.is_unlocked(p));
+    }
+
+    fn is_unlocked(&self, processer: &Processer) -> bool {
+        self.machines.contains(processer.machine())
+            && (!processer.use_speed_module() || self.modules.contains("speed-module"))
+            && (!processer.use_prod_module() || self.modules.contains("productivity-module"))
+            && (!processer.use_beacon() || self.modules.contains("beacon"))
+    }
+}
+
+pub fn load_tech_tree(file_path: &str) -> Result<TechTree> {
+    let file = fs::File::open(file_path)?;
+    let reader = BufReader::new(file);
+
+    let tree: TechTree = serde_yaml::from_reader(reader)?;
+    Ok(tree)
+}
@@ -0,0 +1,84 @@
+use super::*;
+
+use crate::recipe::load_recipes;
+
+fn tech_tree() -> TechTree {
+    load_tech_tree("./data/technologies.yaml").unwrap()
+}
+
+fn tree_of(yaml: &str) -> TechTree {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn unlocks_should_include_prerequisites_and_start() {
+    let tree = tree_of(
+        r#"
+start:
+  recipes: [iron-plate]
+  machines: [stone-furnace]
+automation:
+  machines: [assembling-machine-1]
+steel-processing:
+  recipes: [steel]
+automation-2:
+  prerequisites: [automation, steel-processing]
+  machines: [assembling-machine-2]
+"#,
+    );
+
+    let unlocks = tree.unlocks(&["automation-2".to_string()]).unwrap();
+
+    assert!(unlocks.recipes.contains("iron-plate"));
+    assert!(unlocks.recipes.contains("steel"));
+    assert!(unlocks.machines.contains("assembling-machine-1"));
+    assert!(unlocks.machines.contains("assembling-machine-2"));
+}
+
+#[test]
+fn unlocks_should_fail_on_unknown_and_cycle() {
+    let tree = tree_of(
+        r#"
+start: {}
+a:
+  prerequisites: [b]
+b:
+  prerequisites: [a]
+"#,
+    );
+
+    assert!(tree.unlocks(&["c".to_string()]).is_err());
+    assert!(tree.unlocks(&["a".to_string()]).is_err());
+}
+
+#[test]
+fn every_recipe_should_be_unlocked() {
+    let tree = tech_tree();
+    let unlocked = tree.all_recipes();
+
+    let recipe_sets = [
+        load_recipes("./data/recipes").unwrap(),
+        load_recipes("./data/oil").unwrap(),
+    ];
+    for r in recipe_sets.iter().flat_map(RecipeSet::recipes) {
+        assert!(unlocked.contains(r.name().as_str()), "{}", r.name());
+    }
+}
+
+#[test]
+fn filter_should_drop_locked_recipes_and_processers() {
+    let unlocks = tech_tree().unlocks(&["automation".to_string()]).unwrap();
+
+    let mut recipe_set = load_recipes("./data/recipes").unwrap();
+    unlocks.filter_recipes(&mut recipe_set);
+    assert!(!recipe_set.find_recipes("iron-gear-wheel").is_empty());
+    assert!(recipe_set.find_recipes("steel").is_empty());
+
+    let mut processer_set = ProcSet::open_set().unwrap();
+    unlocks.filter_processers(&mut processer_set);
+    let choice = crate::processer::ProcesserChoice::new();
+    let p = processer_set
+        .best_processer("assembler", 2, true, 100.0, &choice)
+        .unwrap();
+    assert_eq!(p.name(), "assembling-machine-1");
+}
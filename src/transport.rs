@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use failure::{format_err, Error};
+
+use crate::consts::{CARGO_WAGON_STACKS, FLUID_WAGON_CAPACITY};
+use crate::solution::Throughput;
+use crate::stack::StackDict;
+
+#[cfg(test)]
+mod tests;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Train composition written as `locomotives-wagons`, e.g. `1-4` or `2-8`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Train {
+    pub locomotives: u64,
+    pub wagons: u64,
+}
+
+impl FromStr for Train {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Train> {
+        let mut parts = s.splitn(2, '-');
+        let locomotives = parts.next().unwrap_or("");
+        let wagons = parts
+            .next()
+            .ok_or_else(|| format_err!("train must be like 1-4: {}", s))?;
+
+        let train = Train {
+            locomotives: locomotives.trim().parse()?,
+            wagons: wagons.trim().parse()?,
+        };
+        if train.locomotives == 0 || train.wagons == 0 {
+            return Err(format_err!("train needs locomotives and wagons: {}", s));
+        }
+
+        Ok(train)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shipment {
+    pub name: String,
+    pub per_min: f64,
+    pub liquid: bool,
+    pub wagons_per_min: f64,
+}
+
+/// Trains of one wagon kind, a train carries either cargo or fluid wagons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trains {
+    pub wagons_per_min: f64,
+    pub trains_per_min: f64,
+    /// Trains running at once to keep up with a round trip.
+    pub trains: u64,
+    /// Wagons per minute the trains can move, at least `wagons_per_min`.
+    pub capacity_per_min: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transport {
+    pub shipments: Vec<Shipment>,
+    pub cargo: Trains,
+    pub fluid: Trains,
+}
+
+impl Transport {
+    pub fn wagons_per_min(&self) -> f64 {
+        self.cargo.wagons_per_min + self.fluid.wagons_per_min
+    }
+}
+
+/// Loads `throughputs` into cargo and fluid wagons of `train` making a `round_trip` in seconds.
+pub fn transport(
+    stack_dict: &StackDict,
+    throughputs: &[Throughput],
+    train: Train,
+    round_trip: f64,
+) -> Result<Transport> {
+    if round_trip <= 0.0 || round_trip.is_nan() {
+        return Err(format_err!("round trip must be positive: {}", round_trip));
+    }
+
+    let shipments = throughputs
+        .iter()
+        .map(|th| shipment(stack_dict, th))
+        .collect::<Result<Vec<_>>>()?;
+
+    let wagons_per_min = |liquid: bool| {
+        shipments
+            .iter()
+            .filter(|s| s.liquid == liquid)
+            .fold(0.0, |sum, s| sum + s.wagons_per_min)
+    };
+    let cargo = trains(wagons_per_min(false), train, round_trip);
+    let fluid = trains(wagons_per_min(true), train, round_trip);
+
+    Ok(Transport {
+        shipments,
+        cargo,
+        fluid,
+    })
+}
+
+fn trains(wagons_per_min: f64, train: Train, round_trip: f64) -> Trains {
+    let trains_per_min = wagons_per_min / train.wagons as f64;
+    let trains = (trains_per_min * round_trip / 60.0).ceil() as u64;
+    let capacity_per_min = (trains * train.wagons) as f64 * 60.0 / round_trip;
+
+    Trains {
+        wagons_per_min,
+        trains_per_min,
+        trains,
+        capacity_per_min,
+    }
+}
+
+fn shipment(stack_dict: &StackDict, th: &Throughput) -> Result<Shipment> {
+    let per_min = th.throughput() * 60.0;
+
+    let (liquid, wagon_capacity) = match th {
+        Throughput::Item(n, _) => {
+            let stack_size = stack_dict
+                .get(n)
+                .ok_or_else(|| format_err!("unknown stack size: {}", n))?;
+            (false, stack_size as f64 * CARGO_WAGON_STACKS)
+        }
+        Throughput::Liquid(_, _) => (true, FLUID_WAGON_CAPACITY),
+        Throughput::Power(n, _) => return Err(format_err!("{} can't be shipped", n)),
+    };
+
+    Ok(Shipment {
+        name: th.name().to_string(),
+        per_min,
+        liquid,
+        wagons_per_min: per_min / wagon_capacity,
+    })
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+use crate::stack::load_stack_dict;
+
+fn stack_dict() -> StackDict {
+    load_stack_dict("./data/stack-size.yaml").unwrap()
+}
+
+#[test]
+fn train_should_parse_composition() {
+    assert_eq!(
+        "2-8".parse::<Train>().unwrap(),
+        Train {
+            locomotives: 2,
+            wagons: 8
+        }
+    );
+
+    assert!("4".parse::<Train>().is_err());
+    assert!("1-0".parse::<Train>().is_err());
+}
+
+#[test]
+fn transport_should_fill_cargo_and_fluid_wagons() {
+    // 4000 plates and 25000 units of water per minute are one wagon each.
+    let throughputs = vec![
+        Throughput::new("iron-plate".to_string(), 4000.0 / 60.0),
+        Throughput::new("water".to_string(), 25000.0 / 60.0),
+    ];
+    let train = Train {
+        locomotives: 1,
+        wagons: 4,
+    };
+
+    let transport = transport(&stack_dict(), &throughputs, train, 120.0).unwrap();
+
+    assert!((transport.wagons_per_min() - 2.0).abs() < 1e-9);
+    assert!(transport.shipments[1].liquid);
+
+    // cargo and fluid wagons can't share a train
+    for trains in &[&transport.cargo, &transport.fluid] {
+        assert!((trains.wagons_per_min - 1.0).abs() < 1e-9);
+        assert!((trains.trains_per_min - 0.25).abs() < 1e-9);
+        assert_eq!(trains.trains, 1);
+        assert!((trains.capacity_per_min - 2.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn transport_should_reject_non_positive_round_trip() {
+    let throughputs = vec![Throughput::new("iron-plate".to_string(), 1.0)];
+    let train = Train {
+        locomotives: 1,
+        wagons: 4,
+    };
+
+    assert!(transport(&stack_dict(), &throughputs, train, 0.0).is_err());
+    assert!(transport(&stack_dict(), &throughputs, train, -60.0).is_err());
+}
+
+#[test]
+fn transport_should_reject_power() {
+    let throughputs = vec![Throughput::new("electricity".to_string(), 10.0)];
+    let train = Train {
+        locomotives: 1,
+        wagons: 4,
+    };
+
+    assert!(transport(&stack_dict(), &throughputs, train, 120.0).is_err());
+}